@@ -0,0 +1,81 @@
+#!/usr/bin/env python3
+
+import sys
+from collections import defaultdict
+from decimal import Decimal
+import requests
+
+SYMBOLS_TO_ADDRS = {
+    "WBTC": "0x2260fac5e5542a773aa44fbcfedf7c193bc2c599",
+    "WETH": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+}
+API_KEY = ""
+SUBGRAPH_ID = "5zvR82QoaXYFyDEKLZ9t6v9adgnptxYpKpSbxtgVENFV"
+URL = f"https://gateway.thegraph.com/api/{API_KEY}/subgraphs/id/{SUBGRAPH_ID}"
+QUERY = """
+query ($addrs: [String!]) {
+  bundle(id: "1") {
+    ethPriceUSD
+  }
+  tokens(where: { id_in: $addrs }) {
+    id
+    derivedETH
+  }
+}
+"""
+
+
+def get_prices_from_addrs(addrs):
+    r = requests.post(URL, json={"query": QUERY, "variables": {"addrs": addrs}})
+    r.raise_for_status()
+
+    body = r.json()
+    if "errors" in body:
+        raise Exception(body["errors"])
+
+    return body["data"]
+
+
+def get_price_map(symbols):
+    addrs = set()
+    for symbol in symbols:
+        if symbol == "ETH":
+            symbol = "WETH"
+        if symbol in SYMBOLS_TO_ADDRS:
+            addrs.add(SYMBOLS_TO_ADDRS[symbol])
+
+    data = get_prices_from_addrs(list(addrs))
+    eth_price = Decimal(data["bundle"]["ethPriceUSD"])
+
+    addrs_to_symbols = {v.lower(): k for k, v in SYMBOLS_TO_ADDRS.items()}
+
+    price_map = defaultdict(lambda: "-")
+    for token in data["tokens"]:
+        addr = token["id"].lower()
+        if addr in addrs_to_symbols:
+            symbol = addrs_to_symbols[addr]
+            price = Decimal(token["derivedETH"]) * eth_price
+            if price < 0:
+                raise Exception("Negative number returned")
+
+            price_map[symbol] = "{:.9f}".format(price).rstrip("0").rstrip(".")
+
+    if "ETH" in symbols:
+        price_map["ETH"] = price_map["WETH"]
+
+    return price_map
+
+
+def main(symbols):
+    # Skips options such as --max-age, which the subgraph has no use for
+    symbols = [symbol for symbol in symbols if not symbol.startswith("--")]
+    price_map = get_price_map(symbols)
+    return ",".join([price_map[symbol] for symbol in symbols])
+
+
+if __name__ == "__main__":
+    try:
+        print(main(sys.argv[1:]))
+    except Exception as e:
+        print(str(e), file=sys.stderr)
+        sys.exit(1)
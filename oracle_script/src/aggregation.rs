@@ -0,0 +1,3830 @@
+//! Aggregation logic used by the oracle script's entry points. It is exposed so off-chain
+//! tooling can simulate exactly the rates the oracle script would produce.
+//!
+//! ```
+//! use dex_source_os::aggregation::{
+//!     aggregate_value, filter_and_medianize, AggregationParams, EvenRule, MissingPolicy,
+//! };
+//!
+//! // Parsed outputs of three validators for a data source serving a single symbol
+//! let ds_outputs = vec![vec![Some(1.0)], vec![Some(1.1)], vec![Some(1.2)]];
+//! let median_rates =
+//!     filter_and_medianize(ds_outputs, 1, 2, 3, 0, EvenRule::Average, MissingPolicy::Drop);
+//! assert_eq!(median_rates, vec![Some(1.1)]);
+//!
+//! // Aggregates the data source median with another data source's median of 1.3
+//! let rates = vec![median_rates[0].unwrap(), 1.3];
+//! let params = AggregationParams {
+//!     minimum_source_count: 2,
+//!     ..Default::default()
+//! };
+//! let value = aggregate_value(&rates, &[1, 1], &params).unwrap();
+//! assert_eq!(value.rate, 1200000000);
+//! ```
+
+use num::FromPrimitive;
+use std::collections::{HashMap, HashSet};
+use std::iter::zip;
+
+use obi::schema::{Declaration, Definition};
+use obi::{OBIDecode, OBISchema};
+use owasm_kit::ext;
+
+use crate::{
+    canonicalize_symbol, get_declared_aggregation_method, get_declared_minimum_source_count,
+    get_display_symbol, get_multiplier, get_source_count, get_sources, is_source_selected, Chain,
+    DataSources, HighPrecisionResponse, Input, Response, ResponseCode, GROUPS, MULTIPLIER,
+};
+
+/// Method used to aggregate the per-source rates of a symbol
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AggregationMethod {
+    Median = 0,
+    TrimmedMean = 1,
+    GeometricMean = 2,
+    Mean = 3,
+    VolumeWeightedMean = 4,
+    HarmonicMean = 5,
+    WinsorizedMedian = 6,
+    Mode = 7,
+}
+
+impl TryFrom<u8> for AggregationMethod {
+    type Error = ResponseCode;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AggregationMethod::Median),
+            1 => Ok(AggregationMethod::TrimmedMean),
+            2 => Ok(AggregationMethod::GeometricMean),
+            3 => Ok(AggregationMethod::Mean),
+            4 => Ok(AggregationMethod::VolumeWeightedMean),
+            5 => Ok(AggregationMethod::HarmonicMean),
+            6 => Ok(AggregationMethod::WinsorizedMedian),
+            7 => Ok(AggregationMethod::Mode),
+            _ => Err(ResponseCode::UnsupportedAggregationMethod),
+        }
+    }
+}
+
+/// Aggregation method requested in the input, kept as its raw code on the wire
+#[derive(OBIDecode, Default, Debug, Copy, Clone, PartialEq)]
+pub struct AggregationMethodCode(pub u8);
+
+impl TryFrom<u8> for AggregationMethodCode {
+    type Error = ResponseCode;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        AggregationMethod::try_from(value).map(|_| AggregationMethodCode(value))
+    }
+}
+
+/// Gets the schema definition shared by the code newtypes, a struct holding the raw `u8` code.
+/// It encodes exactly like the `u8` itself, while the schema still names the code's type
+fn get_code_definition() -> Definition {
+    Definition::Struct {
+        fields: vec![("code".to_string(), u8::declaration())],
+    }
+}
+
+// The derived schema of a tuple struct has no fields, so the code's definition is spelled out
+impl OBISchema for AggregationMethodCode {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        Self::add_definition(Self::declaration(), get_code_definition(), definitions);
+    }
+
+    fn declaration() -> Declaration {
+        "AggregationMethodCode".to_string()
+    }
+}
+
+/// Rounding applied when scaling an aggregated rate to an integer
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RoundingMode {
+    Truncate = 0,
+    Nearest = 1,
+    Ceil = 2,
+}
+
+impl TryFrom<u8> for RoundingMode {
+    type Error = ResponseCode;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RoundingMode::Truncate),
+            1 => Ok(RoundingMode::Nearest),
+            2 => Ok(RoundingMode::Ceil),
+            _ => Err(ResponseCode::UnsupportedRoundingMode),
+        }
+    }
+}
+
+/// Rounding mode requested in the input, kept as its raw code on the wire
+#[derive(OBIDecode, Default, Debug, Copy, Clone, PartialEq)]
+pub struct RoundingModeCode(pub u8);
+
+impl TryFrom<u8> for RoundingModeCode {
+    type Error = ResponseCode;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        RoundingMode::try_from(value).map(|_| RoundingModeCode(value))
+    }
+}
+
+// The derived schema of a tuple struct has no fields, so the code's definition is spelled out
+impl OBISchema for RoundingModeCode {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        Self::add_definition(Self::declaration(), get_code_definition(), definitions);
+    }
+
+    fn declaration() -> Declaration {
+        "RoundingModeCode".to_string()
+    }
+}
+
+/// Rule picking the median of an even-length set from its two middle values
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EvenRule {
+    /// Averages the two middle values, which may not be any reported value
+    Average = 0,
+    /// Takes the lower middle value
+    Lower = 1,
+    /// Takes the upper middle value
+    Upper = 2,
+}
+
+impl TryFrom<u8> for EvenRule {
+    type Error = ResponseCode;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(EvenRule::Average),
+            1 => Ok(EvenRule::Lower),
+            2 => Ok(EvenRule::Upper),
+            _ => Err(ResponseCode::UnsupportedEvenRule),
+        }
+    }
+}
+
+/// Even rule requested in the input, kept as its raw code on the wire
+#[derive(OBIDecode, Default, Debug, Copy, Clone, PartialEq)]
+pub struct EvenRuleCode(pub u8);
+
+impl TryFrom<u8> for EvenRuleCode {
+    type Error = ResponseCode;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        EvenRule::try_from(value).map(|_| EvenRuleCode(value))
+    }
+}
+
+// The derived schema of a tuple struct has no fields, so the code's definition is spelled out
+impl OBISchema for EvenRuleCode {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        Self::add_definition(Self::declaration(), get_code_definition(), definitions);
+    }
+
+    fn declaration() -> Declaration {
+        "EvenRuleCode".to_string()
+    }
+}
+
+/// Gets the median of the values, averaging the two middle values of an even-length set
+pub(crate) fn median(values: &mut [f64]) -> Option<f64> {
+    median_by_rule(values, EvenRule::Average)
+}
+
+/// Gets the median of the values, picking from the two middle values of an even-length set by
+/// the rule. Equal values may sort in either order, which never changes the result
+fn median_by_rule(values: &mut [f64], even_rule: EvenRule) -> Option<f64> {
+    values.sort_by(ext::cmp::fcmp);
+    let mid = values.len() / 2;
+    if values.is_empty() {
+        None
+    } else if values.len().is_multiple_of(2) {
+        Some(match even_rule {
+            EvenRule::Average => (values[mid - 1] + values[mid]) / 2f64,
+            EvenRule::Lower => values[mid - 1],
+            EvenRule::Upper => values[mid],
+        })
+    } else {
+        Some(values[mid])
+    }
+}
+
+/// Drops rates that are more than `threshold` median absolute deviations away from the median.
+/// A threshold of 0 or a zero deviation leaves the rates untouched
+fn reject_outliers(rates: Vec<f64>, threshold: u8) -> Vec<f64> {
+    if threshold == 0 {
+        return rates;
+    }
+
+    let median_rate = match median(&mut rates.clone()) {
+        Some(median_rate) => median_rate,
+        None => return rates,
+    };
+    let mut deviations = rates
+        .iter()
+        .map(|r| (r - median_rate).abs())
+        .collect::<Vec<f64>>();
+    match median(&mut deviations) {
+        Some(mad) if mad > 0f64 => rates
+            .into_iter()
+            .filter(|r| (r - median_rate).abs() <= threshold as f64 * mad)
+            .collect(),
+        _ => rates,
+    }
+}
+
+/// Policy for a column with too few values left to medianize
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MissingPolicy<'a> {
+    /// Reports the column as missing
+    Drop,
+    /// Reports the column's previous value instead, given by column index, or keeps it missing
+    /// if there is none
+    PreviousValue(&'a [Option<f64>]),
+}
+
+/// Filters and medianizes the parsed data source output, breaking even-length ties by the rule
+/// and resolving columns with too few values by the missing policy
+pub fn filter_and_medianize(
+    rates: Vec<Vec<Option<f64>>>,
+    length: usize,
+    min_response: usize,
+    response_count: usize,
+    outlier_threshold: u8,
+    even_rule: EvenRule,
+    missing_policy: MissingPolicy,
+) -> Vec<Option<f64>> {
+    // A majority of the responses actually received is enough when fewer validators responded
+    let min_response = min_response.min(response_count).max(1);
+    (0..length)
+        .map(|i| {
+            // A ragged output missing the column counts as not having a value for it
+            let symbol_rates = rates
+                .iter()
+                .filter_map(|o| o.get(i).copied().flatten())
+                .collect::<Vec<f64>>();
+            if symbol_rates.len() < min_response {
+                match missing_policy {
+                    MissingPolicy::Drop => None,
+                    MissingPolicy::PreviousValue(previous) => previous.get(i).copied().flatten(),
+                }
+            } else {
+                let mut filtered_rates = reject_outliers(symbol_rates, outlier_threshold);
+                median_by_rule(&mut filtered_rates, even_rule)
+            }
+        })
+        .collect::<Vec<Option<f64>>>()
+}
+
+/// Gets the weighted median, i.e. the value at which the cumulative weight crosses half the
+/// total weight. If the cumulative weight lands exactly on half, the two neighbouring values
+/// are averaged so equal weights reproduce the regular median
+fn weighted_median(rates: &[f64], weights: &[u32]) -> Option<f64> {
+    // Equal weights reduce to the regular median
+    if rates.len() == weights.len() && weights.iter().all(|w| *w > 0 && *w == weights[0]) {
+        return median(&mut rates.to_vec());
+    }
+
+    let mut weighted_rates = zip(rates.iter().copied(), weights.iter().copied())
+        .filter(|(_, w)| *w > 0)
+        .collect::<Vec<(f64, u32)>>();
+    weighted_rates.sort_by(|(a, _), (b, _)| ext::cmp::fcmp(a, b));
+
+    let total_weight = weighted_rates.iter().map(|(_, w)| *w as u64).sum::<u64>();
+    let mut cumulative_weight = 0u64;
+    for (i, (rate, weight)) in weighted_rates.iter().enumerate() {
+        cumulative_weight += *weight as u64;
+        if cumulative_weight * 2 == total_weight {
+            return weighted_rates
+                .get(i + 1)
+                .map(|(next, _)| (rate + next) / 2f64);
+        } else if cumulative_weight * 2 > total_weight {
+            return Some(*rate);
+        }
+    }
+    None
+}
+
+/// Gets the weighted median of the rates after clamping them to their 10th and 90th percentile,
+/// so a heavily weighted extreme rate cannot become the median. At least the lowest and highest
+/// rate are clamped, and fewer than four rates fall back to the plain weighted median
+fn winsorized_median(rates: &[f64], weights: &[u32]) -> Option<f64> {
+    if rates.len() < 4 {
+        return weighted_median(rates, weights);
+    }
+
+    let mut sorted_rates = rates.to_owned();
+    sorted_rates.sort_by(ext::cmp::fcmp);
+    let clamped = (sorted_rates.len() / 10).max(1);
+    let lower = sorted_rates[clamped];
+    let upper = sorted_rates[sorted_rates.len() - 1 - clamped];
+    let winsorized_rates = rates
+        .iter()
+        .map(|r| r.max(lower).min(upper))
+        .collect::<Vec<f64>>();
+    weighted_median(&winsorized_rates, weights)
+}
+
+/// Gets the most common rate, counting rates within `MODE_TOLERANCE_BPS` basis points of the
+/// lowest rate of their bucket as equal and reporting each bucket as the mean of its rates. Ties
+/// are broken towards the bucket nearest the median, and then towards the lower bucket
+fn mode(rates: &[f64]) -> Option<f64> {
+    let mut sorted_rates = rates.to_owned();
+    sorted_rates.sort_by(ext::cmp::fcmp);
+    let median_rate = median(&mut sorted_rates.clone())?;
+
+    let mut buckets: Vec<Vec<f64>> = Vec::new();
+    for rate in sorted_rates {
+        match buckets.last_mut() {
+            Some(bucket)
+                if rate - bucket[0] <= bucket[0].abs() * MODE_TOLERANCE_BPS as f64 / 10000f64 =>
+            {
+                bucket.push(rate)
+            }
+            _ => buckets.push(vec![rate]),
+        }
+    }
+    buckets
+        .iter()
+        .filter_map(|bucket| mean(bucket).map(|rate| (bucket.len(), rate)))
+        .reduce(|best, candidate| {
+            let is_better = candidate.0 > best.0
+                || candidate.0 == best.0
+                    && (candidate.1 - median_rate).abs() < (best.1 - median_rate).abs();
+            if is_better {
+                candidate
+            } else {
+                best
+            }
+        })
+        .map(|(_, rate)| rate)
+}
+
+/// Gets the arithmetic mean of the rates
+fn mean(rates: &[f64]) -> Option<f64> {
+    if rates.is_empty() {
+        None
+    } else {
+        Some(rates.iter().sum::<f64>() / rates.len() as f64)
+    }
+}
+
+/// Gets the volume-weighted average price of the rates, or `None` if there is no volume to weight
+/// the rates by
+fn volume_weighted_mean(rates: &[f64], volumes: &[f64]) -> Option<f64> {
+    let total_volume = volumes.iter().sum::<f64>();
+    if rates.len() != volumes.len() || total_volume <= 0f64 {
+        None
+    } else {
+        Some(zip(rates, volumes).map(|(r, v)| r * v).sum::<f64>() / total_volume)
+    }
+}
+
+/// Gets the mean of the rates after dropping the highest and lowest value. Falls back to the
+/// plain mean when fewer than three rates are given
+fn trimmed_mean(rates: &[f64]) -> Option<f64> {
+    if rates.len() < 3 {
+        mean(rates)
+    } else {
+        let mut sorted_rates = rates.to_owned();
+        sorted_rates.sort_by(ext::cmp::fcmp);
+        mean(&sorted_rates[1..sorted_rates.len() - 1])
+    }
+}
+
+/// Gets the geometric mean of the rates, i.e. the nth root of their product. Computed in log
+/// space to avoid overflowing the product
+fn geometric_mean(rates: &[f64]) -> Option<f64> {
+    let log_rates = rates.iter().map(|r| r.ln()).collect::<Vec<f64>>();
+    mean(&log_rates).map(f64::exp)
+}
+
+/// Gets the harmonic mean of the rates, i.e. `n / sum(1 / rate)`
+fn harmonic_mean(rates: &[f64]) -> Option<f64> {
+    let reciprocals = rates.iter().map(|r| 1f64 / r).collect::<Vec<f64>>();
+    mean(&reciprocals).map(|mean| 1f64 / mean)
+}
+
+/// The aggregated rate of a symbol along with the spread of the rates it was aggregated from
+#[derive(PartialEq, Debug)]
+pub struct AggregatedValue {
+    pub rate: u64,
+    pub spread: u64,
+    /// Mean of the rates scaled like the rate, or 0 if it cannot be scaled
+    pub secondary_rate: u64,
+    /// Sample standard deviation of the rates scaled by `MULTIPLIER`
+    pub std_dev: u64,
+    /// Whether the rate is the median of fewer sources than required
+    pub degraded: bool,
+    /// Whether the rate is usable but aggregated from only the required number of sources or
+    /// from sources spread beyond `WARNING_SPREAD_PERCENT`
+    pub warning: bool,
+}
+
+/// Gets the spread of the rates relative to their aggregated rate, i.e. `(max - min) / rate`,
+/// scaled by `MULTIPLIER`. Returns 0 when fewer than two rates are given
+fn get_spread(rates: &[f64], rate: f64) -> u64 {
+    if rates.len() < 2 {
+        return 0;
+    }
+
+    let max = rates.iter().copied().fold(f64::MIN, f64::max);
+    let min = rates.iter().copied().fold(f64::MAX, f64::min);
+    if max == min {
+        0
+    } else {
+        u64::from_f64((max - min) / rate * MULTIPLIER as f64).unwrap_or(u64::MAX)
+    }
+}
+
+/// Gets the sample standard deviation of the rates once converted like the reported rate and
+/// scaled by its multiplier. Returns 0 when fewer than two rates are given or a rate cannot be
+/// converted
+fn get_std_dev(rates: &[f64], params: &AggregationParams) -> u64 {
+    if rates.len() < 2 {
+        return 0;
+    }
+    let Ok(rates) = rates
+        .iter()
+        .map(|rate| convert_rate(*rate, params))
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        return 0;
+    };
+
+    let mean = rates.iter().sum::<f64>() / rates.len() as f64;
+    let variance = rates.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (rates.len() - 1) as f64;
+    u64::from_f64(variance.sqrt() * params.multiplier as f64).unwrap_or(u64::MAX)
+}
+
+/// Checks whether the largest rate deviates from the smallest by more than the given basis points
+fn exceeds_deviation(rates: &[f64], max_deviation_bps: u16) -> bool {
+    if max_deviation_bps == 0 || rates.len() < 2 {
+        return false;
+    }
+
+    let max = rates.iter().copied().fold(f64::MIN, f64::max);
+    let min = rates.iter().copied().fold(f64::MAX, f64::min);
+    if min <= 0f64 {
+        return max > min;
+    }
+    (max - min) / min * 10000f64 > max_deviation_bps as f64
+}
+
+/// Checks whether a scaled rate moved more than `max_jump_percent` percent away from the
+/// previous rate. A previous rate or maximum of 0 disables the check
+fn is_price_jump(rate: u128, previous_rate: u64, max_jump_percent: u16) -> bool {
+    if previous_rate == 0 || max_jump_percent == 0 {
+        return false;
+    }
+    rate.abs_diff(previous_rate as u128).saturating_mul(100)
+        > previous_rate as u128 * max_jump_percent as u128
+}
+
+/// Gets the signed deviation of a scaled rate from a nonzero reference rate in basis points,
+/// truncated towards zero and saturated at the bounds of an i32
+fn get_deviation_bps(rate: u64, reference_rate: u64) -> i32 {
+    let deviation = (rate as i128 - reference_rate as i128) * 10000 / reference_rate as i128;
+    deviation.clamp(i32::MIN as i128, i32::MAX as i128) as i32
+}
+
+/// Parameters controlling how a symbol's rates are aggregated
+#[derive(Debug, Clone, Copy)]
+pub struct AggregationParams<'a> {
+    pub minimum_source_count: usize,
+    /// Minimum source count declared by the symbol, enforced even if the caller asks for fewer
+    pub declared_minimum_source_count: usize,
+    pub multiplier: u64,
+    pub aggregation_method: u8,
+    /// Reports the reciprocal of the aggregated rate
+    pub invert: bool,
+    /// Maximum deviation allowed between the sources in basis points, where 0 disables the check
+    pub max_deviation_bps: u16,
+    /// Rate of the quote symbol the aggregated rate is divided by before scaling
+    pub quote_rate: Option<f64>,
+    /// Rounding applied when scaling the rate, as a `RoundingMode`
+    pub rounding: u8,
+    /// Traded volume of each rate, used by `AggregationMethod::VolumeWeightedMean`
+    pub volumes: Option<&'a [f64]>,
+    /// Previously reported scaled rate the new rate is checked against, where 0 disables the check
+    pub previous_rate: u64,
+    /// Maximum move allowed from the previous rate in percent, where 0 disables the check
+    pub max_jump_percent: u16,
+    /// Falls back to the median of the available sources when fewer than required report
+    pub allow_degraded: bool,
+    /// Flags rates aggregated from only the required number of sources or from widely spread
+    /// sources with a warning
+    pub report_warnings: bool,
+}
+
+impl Default for AggregationParams<'_> {
+    fn default() -> Self {
+        AggregationParams {
+            minimum_source_count: 0,
+            declared_minimum_source_count: 0,
+            multiplier: MULTIPLIER,
+            aggregation_method: AggregationMethod::Median as u8,
+            invert: false,
+            max_deviation_bps: 0,
+            quote_rate: None,
+            rounding: RoundingMode::Truncate as u8,
+            volumes: None,
+            previous_rate: 0,
+            max_jump_percent: 0,
+            allow_degraded: false,
+            report_warnings: false,
+        }
+    }
+}
+
+/// Number of sources every symbol requires, whatever count or percentage is configured
+const MIN_ABSOLUTE_SOURCES: usize = 1;
+
+/// Gets the number of sources a symbol requires given the caller's and the symbol's own minimum.
+/// At least `MIN_ABSOLUTE_SOURCES` are always required, even if both ask for fewer
+fn get_required_source_count(
+    minimum_source_count: usize,
+    declared_minimum_source_count: usize,
+) -> usize {
+    minimum_source_count
+        .max(declared_minimum_source_count)
+        .max(MIN_ABSOLUTE_SOURCES)
+}
+
+/// Aggregates the data sources outputs to an unscaled rate or error
+pub fn aggregate_rate(
+    rates: &[f64],
+    weights: &[u32],
+    params: &AggregationParams,
+) -> Result<f64, ResponseCode> {
+    // Sources that fundamentally disagree make any aggregate meaningless
+    if exceeds_deviation(rates, params.max_deviation_bps) {
+        return Err(ResponseCode::SourceDisagreement);
+    }
+
+    let aggregated_rate = match AggregationMethod::try_from(params.aggregation_method)? {
+        AggregationMethod::Median => weighted_median(rates, weights),
+        AggregationMethod::TrimmedMean => trimmed_mean(rates),
+        AggregationMethod::WinsorizedMedian => winsorized_median(rates, weights),
+        AggregationMethod::Mode => mode(rates),
+        AggregationMethod::GeometricMean => {
+            // The geometric mean is meaningless if any rate is not positive
+            if rates.iter().any(|r| *r <= 0f64) {
+                return Err(ResponseCode::ConversionError);
+            }
+            geometric_mean(rates)
+        }
+        AggregationMethod::Mean => mean(rates),
+        // Falls back to the median if the sources did not report volumes
+        AggregationMethod::VolumeWeightedMean => params
+            .volumes
+            .and_then(|volumes| volume_weighted_mean(rates, volumes))
+            .or_else(|| weighted_median(rates, weights)),
+        AggregationMethod::HarmonicMean => {
+            // The reciprocal of a zero rate is undefined
+            if rates.contains(&0f64) {
+                return Err(ResponseCode::ConversionError);
+            }
+            harmonic_mean(rates)
+        }
+    };
+    // Falls back to the mean if the method finds no rate, e.g. when every source weighs nothing
+    let aggregated_rate = aggregated_rate.or_else(|| {
+        if rates.iter().all(|r| r.is_finite()) {
+            mean(rates)
+        } else {
+            None
+        }
+    });
+
+    let minimum_source_count = get_required_source_count(
+        params.minimum_source_count,
+        params.declared_minimum_source_count,
+    );
+    if rates.len() < minimum_source_count {
+        return Err(ResponseCode::NotEnoughSources);
+    }
+    // Empty rates are not expected here as at least one source is always required, so non-finite
+    // rates are what leaves no rate. Unknown is kept for finite rates that still leave none
+    match aggregated_rate {
+        Some(aggregated_rate) => Ok(aggregated_rate),
+        None if rates.is_empty() => Err(ResponseCode::EmptyData),
+        None if rates.iter().any(|r| !r.is_finite()) => Err(ResponseCode::InvalidNumber),
+        None => Err(ResponseCode::Unknown),
+    }
+}
+
+/// Aggregates the data sources outputs by each of the aggregation methods in turn, reporting 0
+/// for a method that fails
+pub fn aggregate_values(
+    rates: &[f64],
+    weights: &[u32],
+    params: &AggregationParams,
+    methods: &[AggregationMethodCode],
+) -> Vec<u64> {
+    methods
+        .iter()
+        .map(|method| {
+            let params = AggregationParams {
+                aggregation_method: method.0,
+                ..*params
+            };
+            aggregate_value(rates, weights, &params).map_or(0, |value| value.rate)
+        })
+        .collect()
+}
+
+/// Aggregates the data sources outputs to either a result or error
+pub fn aggregate_value(
+    rates: &[f64],
+    weights: &[u32],
+    params: &AggregationParams,
+) -> Result<AggregatedValue, ResponseCode> {
+    let required_source_count = get_required_source_count(
+        params.minimum_source_count,
+        params.declared_minimum_source_count,
+    );
+    let degraded =
+        params.allow_degraded && !rates.is_empty() && rates.len() < required_source_count;
+    let price = if degraded {
+        aggregate_rate(
+            rates,
+            weights,
+            &AggregationParams {
+                minimum_source_count: 0,
+                declared_minimum_source_count: 0,
+                aggregation_method: AggregationMethod::Median as u8,
+                ..*params
+            },
+        )?
+    } else {
+        aggregate_rate(rates, weights, params)?
+    };
+    let spread = get_spread(rates, price);
+    let rate = finalize_rate(price, params)?;
+    if is_price_jump(rate as u128, params.previous_rate, params.max_jump_percent) {
+        return Err(ResponseCode::PriceJump);
+    }
+    let secondary_rate = mean(rates)
+        .and_then(|mean| finalize_rate(mean, params).ok())
+        .unwrap_or(0);
+    let warning = params.report_warnings
+        && !degraded
+        && (rates.len() == required_source_count
+            || spread > MULTIPLIER / 100 * WARNING_SPREAD_PERCENT);
+    Ok(AggregatedValue {
+        rate,
+        spread,
+        secondary_rate,
+        std_dev: get_std_dev(rates, params),
+        degraded,
+        warning,
+    })
+}
+
+/// Aggregates the data sources outputs to a rate scaled into a u128, which fits rates with a
+/// large multiplier that overflow a u64. The rate still carries only the precision of an f64
+pub fn aggregate_high_precision_value(
+    rates: &[f64],
+    weights: &[u32],
+    params: &AggregationParams,
+) -> Result<u128, ResponseCode> {
+    let price = aggregate_rate(rates, weights, params)?;
+    let rate = scale_rate(price, params)?;
+    if is_price_jump(rate, params.previous_rate, params.max_jump_percent) {
+        return Err(ResponseCode::PriceJump);
+    }
+    Ok(rate)
+}
+
+/// Finalizes an unscaled aggregate into the reported rate, shared by every aggregation path so
+/// they fail alike. A non-finite rate, e.g. from dividing by a near-zero rate, is
+/// `InvalidNumber`, while a finite one that does not fit a u64 once scaled is `Overflow`
+fn finalize_rate(value: f64, params: &AggregationParams) -> Result<u64, ResponseCode> {
+    u64::try_from(scale_rate(value, params)?).map_err(|_| ResponseCode::Overflow)
+}
+
+/// Converts an unscaled rate to the quote symbol and inverts it if requested
+fn convert_rate(price: f64, params: &AggregationParams) -> Result<f64, ResponseCode> {
+    // Expresses the rate in terms of the quote symbol
+    let price = match params.quote_rate {
+        Some(0f64) => return Err(ResponseCode::ConversionError),
+        Some(quote_rate) => price / quote_rate,
+        None => price,
+    };
+
+    if params.invert {
+        if price == 0f64 {
+            return Err(ResponseCode::ConversionError);
+        }
+        Ok(1f64 / price)
+    } else {
+        Ok(price)
+    }
+}
+
+/// Converts an unscaled rate to the quote symbol, inverts it if requested and scales it with the
+/// requested rounding
+fn scale_rate(price: f64, params: &AggregationParams) -> Result<u128, ResponseCode> {
+    // Inverts the aggregate rather than the individual sources
+    let rate = convert_rate(price, params)?;
+
+    // A non-finite aggregate is invalid, a negative one cannot be a price, while a finite one
+    // that does not fit the integer type overflows
+    if !rate.is_finite() {
+        return Err(ResponseCode::InvalidNumber);
+    }
+    if rate < 0f64 {
+        return Err(ResponseCode::NegativeRate);
+    }
+    let rounding = RoundingMode::try_from(params.rounding)?;
+    if let Some(scaled_rate) = scale_decimal(rate, params.multiplier, rounding) {
+        return Ok(scaled_rate);
+    }
+    let scaled_rate = rate * params.multiplier as f64;
+    let scaled_rate = match rounding {
+        RoundingMode::Truncate => scaled_rate,
+        RoundingMode::Nearest => scaled_rate.round(),
+        RoundingMode::Ceil => scaled_rate.ceil(),
+    };
+    u128::from_f64(scaled_rate).ok_or(ResponseCode::Overflow)
+}
+
+/// Formats a rate scaled by a power of ten multiplier as a decimal string with one digit per
+/// decimal place of the multiplier, e.g. `1234000000` scaled by `1e9` as `"1.234000000"`
+fn format_rate(rate: u64, multiplier: u64) -> String {
+    let decimals = multiplier.checked_ilog10().unwrap_or(0) as usize;
+    if decimals == 0 {
+        return rate.to_string();
+    }
+    format!(
+        "{}.{:0decimals$}",
+        rate / multiplier,
+        rate % multiplier,
+        decimals = decimals
+    )
+}
+
+/// Scales the rate by a power of ten multiplier exactly, using integer arithmetic on the shortest
+/// decimal representation of the rate so no f64 rounding creeps in near the integer limits.
+/// Returns `None` if the multiplier is not a power of ten or the rate does not fit
+fn scale_decimal(rate: f64, multiplier: u64, rounding: RoundingMode) -> Option<u128> {
+    let scale = multiplier.checked_ilog10()? as usize;
+    if 10u64.pow(scale as u32) != multiplier {
+        return None;
+    }
+
+    // The display of an f64 never uses an exponent, so it is always `digits[.digits]`
+    let decimal = rate.to_string();
+    let (integer, fraction) = decimal.split_once('.').unwrap_or((&decimal, ""));
+    if !integer.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (kept, dropped) = fraction.split_at(fraction.len().min(scale));
+    let digits = format!("{}{}{}", integer, kept, "0".repeat(scale - kept.len()));
+    let scaled_rate = digits.parse::<u128>().ok()?;
+    let round_up = match rounding {
+        RoundingMode::Truncate => false,
+        RoundingMode::Nearest => dropped.bytes().next().is_some_and(|b| b >= b'5'),
+        RoundingMode::Ceil => dropped.bytes().any(|b| b != b'0'),
+    };
+    scaled_rate.checked_add(round_up as u128)
+}
+
+/// Minimum source counts above this value are read as a percentage of the available sources
+pub(crate) const PERCENTAGE_OFFSET: usize = 100;
+
+/// Tolerance in basis points within which rates count as the same value for the mode
+const MODE_TOLERANCE_BPS: u64 = 1;
+
+/// Spread in percent of the aggregated rate above which a rate is flagged with a warning
+const WARNING_SPREAD_PERCENT: u64 = 5;
+
+/// Gets the minimum source count for the symbol at the given index, falling back to the global
+/// minimum source count when no override is given. Values above `PERCENTAGE_OFFSET` require
+/// `value - PERCENTAGE_OFFSET` percent of the symbol's available sources, rounded up
+fn get_minimum_source_count(
+    index: usize,
+    minimum_source_counts: &[u8],
+    minimum_source_count: u8,
+    available_source_count: usize,
+) -> usize {
+    let count = *minimum_source_counts
+        .get(index)
+        .unwrap_or(&minimum_source_count) as usize;
+    if count <= PERCENTAGE_OFFSET {
+        return count;
+    }
+    let percent = (count - PERCENTAGE_OFFSET).min(100);
+    (available_source_count * percent).div_ceil(100)
+}
+
+/// Data collected from the data sources for the requested symbols, keyed by canonical symbol
+#[derive(Default, Debug)]
+pub struct SourceData {
+    /// Median rates of each symbol from each data source that produced one
+    pub prices: HashMap<String, Vec<(DataSources, f64)>>,
+    /// Median traded volumes of each symbol from each data source that reported them
+    pub volumes: HashMap<String, Vec<(DataSources, f64)>>,
+    /// Symbols fed by at least one data source whose outputs look stale
+    pub stale_symbols: HashSet<String>,
+    /// Symbols fed by at least one data source that too few of the asked validators responded to
+    pub low_participation_symbols: HashSet<String>,
+    /// Symbols for which at least one validator reported data
+    pub received_symbols: HashSet<String>,
+    /// Symbols for which at least one validator's report could be parsed
+    pub parsed_symbols: HashSet<String>,
+    /// Symbols fed by at least one data source that responded with only null prices
+    pub empty_symbols: HashSet<String>,
+    /// Number of malformed report rows discarded from the data sources of each symbol
+    pub discarded_rows: HashMap<String, usize>,
+}
+
+impl SourceData {
+    /// Checks whether the symbol received data but none of it could be parsed
+    pub fn is_unparsable(&self, symbol: &str) -> bool {
+        self.received_symbols.contains(symbol) && !self.parsed_symbols.contains(symbol)
+    }
+
+    /// Checks whether the symbol has no price because its data sources responded with only nulls
+    pub fn is_source_empty(&self, symbol: &str) -> bool {
+        self.empty_symbols.contains(symbol) && !self.prices.contains_key(symbol)
+    }
+}
+
+/// Returns the chain shared by all contributing data sources, or `Chain::Mixed` if they differ
+fn get_chain(prices: &[(DataSources, f64)]) -> Chain {
+    let mut chains = prices.iter().map(|(ds, _)| ds.chain());
+    match chains.next() {
+        Some(first) if chains.all(|chain| chain == first) => first,
+        _ => Chain::Mixed,
+    }
+}
+
+/// Aggregates the quote symbol's rate, or returns `None` if no quote symbol was requested. A quote
+/// that cannot be aggregated is reported as a `ConversionError`
+fn get_quote_rate(
+    quote_symbol: &str,
+    source_data: &SourceData,
+    input: &Input,
+) -> Option<Result<f64, ResponseCode>> {
+    if quote_symbol.is_empty() {
+        return None;
+    }
+
+    // An unknown quote symbol has no prices and fails like a missing one
+    let symbol = canonicalize_symbol(quote_symbol).unwrap_or_default();
+    let quote_rate = match source_data.prices.get(symbol) {
+        Some(_) if source_data.stale_symbols.contains(symbol) => Err(ResponseCode::ConversionError),
+        Some(prices) => {
+            let (rates, weights): (Vec<f64>, Vec<u32>) =
+                prices.iter().map(|(ds, rate)| (*rate, ds.weight())).unzip();
+            // The quote symbol has no index in the request, so only the request-wide minimum
+            // applies to it along with its declared one
+            let params = AggregationParams {
+                minimum_source_count: get_minimum_source_count(
+                    0,
+                    &[],
+                    input.minimum_source_count,
+                    get_source_count(symbol),
+                ),
+                declared_minimum_source_count: get_declared_minimum_source_count(symbol),
+                aggregation_method: get_aggregation_method(symbol, input),
+                max_deviation_bps: input.max_deviation_bps,
+                ..Default::default()
+            };
+            aggregate_rate(&rates, &weights, &params).map_err(|_| ResponseCode::ConversionError)
+        }
+        None => Err(ResponseCode::ConversionError),
+    };
+    Some(quote_rate)
+}
+
+/// Checks whether the symbol is left out of the output because it is unsupported
+fn is_skipped(symbol: &str, input: &Input) -> bool {
+    input.skip_unsupported && canonicalize_symbol(symbol).is_none()
+}
+
+/// Gets the volume of each of the symbol's prices, or `None` unless every price has one
+fn get_volumes(
+    prices: &[(DataSources, f64)],
+    volumes: Option<&Vec<(DataSources, f64)>>,
+) -> Option<Vec<f64>> {
+    let volumes = volumes?;
+    prices
+        .iter()
+        .map(|(ds, _)| {
+            volumes
+                .iter()
+                .find(|(volume_ds, _)| volume_ds == ds)
+                .map(|(_, volume)| *volume)
+        })
+        .collect()
+}
+
+/// Gets the aggregation method of the canonical symbol, preferring its declared method unless the
+/// caller overrides it
+fn get_aggregation_method(symbol: &str, input: &Input) -> u8 {
+    match get_declared_aggregation_method(symbol) {
+        Some(method) if !input.override_method => method as u8,
+        _ => input.aggregation_method.0,
+    }
+}
+
+/// Gets the aggregation parameters of the canonical symbol at the given index of the request
+fn get_aggregation_params<'a>(
+    i: usize,
+    symbol: &str,
+    input: &Input,
+    quote_rate: Option<f64>,
+    volumes: Option<&'a [f64]>,
+) -> AggregationParams<'a> {
+    AggregationParams {
+        minimum_source_count: get_minimum_source_count(
+            i,
+            &input.minimum_source_counts,
+            input.minimum_source_count,
+            get_source_count(symbol),
+        ),
+        declared_minimum_source_count: get_declared_minimum_source_count(symbol),
+        multiplier: get_multiplier(symbol),
+        aggregation_method: get_aggregation_method(symbol, input),
+        invert: input.invert.get(i).copied().unwrap_or(false),
+        max_deviation_bps: input.max_deviation_bps,
+        quote_rate,
+        rounding: input.rounding.0,
+        volumes,
+        previous_rate: input.previous_rates.get(i).copied().unwrap_or(0),
+        max_jump_percent: input.max_jump_percent,
+        allow_degraded: input.allow_degraded,
+        report_warnings: input.report_warnings,
+    }
+}
+
+/// Gets the prices of the canonical symbol at the given index from the sources its mask selects
+fn get_selected_prices(
+    i: usize,
+    symbol: &str,
+    source_data: &SourceData,
+    input: &Input,
+) -> Option<Vec<(DataSources, f64)>> {
+    let source_mask = input.source_masks.get(i).copied().unwrap_or(0);
+    source_data.prices.get(symbol).map(|prices| {
+        prices
+            .iter()
+            .filter(|(ds, _)| is_source_selected(symbol, *ds, source_mask))
+            .copied()
+            .collect()
+    })
+}
+
+/// Gets the response of the symbol at the given index of the request
+fn get_response(
+    i: usize,
+    symbol: &str,
+    source_data: &SourceData,
+    input: &Input,
+    quote_rate: Option<Result<f64, ResponseCode>>,
+) -> Response {
+    // Looks up the canonical symbol but echoes back the requested name or alias
+    let name = get_display_symbol(symbol);
+    let symbol = &canonicalize_symbol(symbol)
+        .map(String::from)
+        .unwrap_or_else(|| symbol.to_string());
+    if source_data.is_unparsable(symbol) {
+        return Response::new(name.clone(), ResponseCode::ParseError, 0, 0);
+    }
+    if source_data.is_source_empty(symbol) {
+        return Response::new(name.clone(), ResponseCode::SourceEmpty, 0, 0);
+    }
+
+    if let Some(prices) = get_selected_prices(i, symbol, source_data, input) {
+        let (rates, weights): (Vec<f64>, Vec<u32>) =
+            prices.iter().map(|(ds, rate)| (*rate, ds.weight())).unzip();
+        let source_count = u8::try_from(prices.len()).unwrap_or(u8::MAX);
+        let chain = get_chain(&prices);
+        let volumes = get_volumes(&prices, source_data.volumes.get(symbol));
+        let quote_rate = match quote_rate {
+            Some(Ok(quote_rate)) => Some(quote_rate),
+            Some(Err(code)) => {
+                return Response::new(name, code, 0, source_count).with_chain_id(chain)
+            }
+            None => None,
+        };
+        let params = get_aggregation_params(i, symbol, input, quote_rate, volumes.as_deref());
+        let response = match aggregate_value(&rates, &weights, &params) {
+            Ok(_) if source_data.stale_symbols.contains(symbol) => {
+                Response::new(name.clone(), ResponseCode::StaleData, 0, source_count)
+            }
+            Ok(_) if source_data.low_participation_symbols.contains(symbol) => Response::new(
+                name.clone(),
+                ResponseCode::LowParticipation,
+                0,
+                source_count,
+            ),
+            Ok(value) => {
+                let code = if value.degraded {
+                    ResponseCode::Degraded
+                } else if value.warning {
+                    ResponseCode::SuccessWithWarning
+                } else {
+                    ResponseCode::Success
+                };
+                let response = Response::new(name.clone(), code, value.rate, source_count)
+                    .with_spread(value.spread);
+                let response = if input.include_secondary {
+                    response.with_secondary_rate(value.secondary_rate)
+                } else {
+                    response
+                };
+                let response = if input.include_stats {
+                    response.with_std_dev(value.std_dev)
+                } else {
+                    response
+                };
+                let response = if input.string_rates {
+                    response.with_rate_str(format_rate(value.rate, params.multiplier))
+                } else {
+                    response
+                };
+                let response = match input.reference_rates.get(i).copied().unwrap_or(0) {
+                    reference_rate if input.report_deviation && reference_rate != 0 => {
+                        response.with_deviation_bps(get_deviation_bps(value.rate, reference_rate))
+                    }
+                    _ => response,
+                };
+                if input.methods.is_empty() {
+                    response
+                } else {
+                    response.with_method_rates(aggregate_values(
+                        &rates,
+                        &weights,
+                        &params,
+                        &input.methods,
+                    ))
+                }
+            }
+            Err(code) => Response::new(name.clone(), code, 0, source_count),
+        };
+        response.with_chain_id(chain)
+    } else {
+        Response::new(name.clone(), ResponseCode::SymbolNotSupported, 0, 0)
+    }
+}
+
+/// Gets the high precision response of the symbol at the given index from its regular response.
+/// The rate is aggregated again as a u128 if the regular response succeeded, possibly with a
+/// warning, or overflowed, while any other failure is kept as is
+fn get_high_precision_response(
+    i: usize,
+    symbol: &str,
+    source_data: &SourceData,
+    input: &Input,
+    quote_rate: Option<Result<f64, ResponseCode>>,
+    response: &Response,
+) -> HighPrecisionResponse {
+    let code = ResponseCode::from(response.response_code);
+    let symbol = canonicalize_symbol(symbol).unwrap_or_default();
+    let prices = match get_selected_prices(i, symbol, source_data, input) {
+        Some(prices)
+            if matches!(
+                code,
+                ResponseCode::Success | ResponseCode::SuccessWithWarning | ResponseCode::Overflow
+            ) =>
+        {
+            prices
+        }
+        _ => return HighPrecisionResponse::new(response.symbol.clone(), code, 0),
+    };
+    let (rates, weights): (Vec<f64>, Vec<u32>) =
+        prices.iter().map(|(ds, rate)| (*rate, ds.weight())).unzip();
+    let volumes = get_volumes(&prices, source_data.volumes.get(symbol));
+    let quote_rate = quote_rate.and_then(Result::ok);
+    let params = get_aggregation_params(i, symbol, input, quote_rate, volumes.as_deref());
+    match aggregate_high_precision_value(&rates, &weights, &params) {
+        Ok(rate) if code == ResponseCode::SuccessWithWarning => {
+            HighPrecisionResponse::new(response.symbol.clone(), code, rate)
+        }
+        Ok(rate) => {
+            HighPrecisionResponse::new(response.symbol.clone(), ResponseCode::Success, rate)
+        }
+        Err(code) => HighPrecisionResponse::new(response.symbol.clone(), code, 0),
+    }
+}
+
+/// Describes why a response failed, or returns an empty string if it succeeded
+fn get_failure_reason(
+    code: ResponseCode,
+    source_count: u8,
+    required_source_count: usize,
+) -> String {
+    match code {
+        ResponseCode::Success | ResponseCode::Health => String::new(),
+        ResponseCode::SuccessWithWarning => {
+            "rate rests on only the required sources or widely spread ones".to_string()
+        }
+        ResponseCode::SymbolNotSupported => "symbol is not supported".to_string(),
+        ResponseCode::NotEnoughSources => format!(
+            "only {} of {} sources responded",
+            source_count, required_source_count
+        ),
+        ResponseCode::Degraded => format!(
+            "only {} of {} sources responded, reporting their median",
+            source_count, required_source_count
+        ),
+        ResponseCode::ConversionError => "rate could not be converted".to_string(),
+        ResponseCode::NegativeRate => "rate is negative".to_string(),
+        ResponseCode::UnsupportedAggregationMethod => {
+            "aggregation method is not supported".to_string()
+        }
+        ResponseCode::StaleData => "source outputs are stale".to_string(),
+        ResponseCode::ParseError => "source outputs could not be parsed".to_string(),
+        ResponseCode::SourceEmpty => "sources responded without any values".to_string(),
+        ResponseCode::EmptyData => "no source rates were left to aggregate".to_string(),
+        ResponseCode::InvalidMaxAge => "max age is out of range".to_string(),
+        ResponseCode::TooManySymbols => "too many symbols requested".to_string(),
+        ResponseCode::SourceDisagreement => {
+            "sources disagree beyond the allowed deviation".to_string()
+        }
+        ResponseCode::LowParticipation => "too few validators responded".to_string(),
+        ResponseCode::Overflow => "rate overflows when scaled".to_string(),
+        ResponseCode::InvalidNumber => "rate is not a finite number".to_string(),
+        ResponseCode::UnsupportedRoundingMode => "rounding mode is not supported".to_string(),
+        ResponseCode::UnsupportedEvenRule => "even rule is not supported".to_string(),
+        ResponseCode::EmptyRequest => "no symbols requested".to_string(),
+        ResponseCode::PriceJump => "rate moved too far from the previous rate".to_string(),
+        ResponseCode::BatchRejected => "another symbol in the batch failed".to_string(),
+        ResponseCode::Unknown => "aggregation found no rate for finite source rates".to_string(),
+    }
+}
+
+/// Gets the unscaled rate of the canonical symbol at the given index of the request, before it is
+/// inverted or expressed in terms of a quote symbol
+fn get_base_rate(i: usize, symbol: &str, source_data: &SourceData, input: &Input) -> Option<f64> {
+    let prices = get_selected_prices(i, symbol, source_data, input)?;
+    let (rates, weights): (Vec<f64>, Vec<u32>) =
+        prices.iter().map(|(ds, rate)| (*rate, ds.weight())).unzip();
+    let volumes = get_volumes(&prices, source_data.volumes.get(symbol));
+    // The previous rate is in terms of the reported rate, so the jump check is left out too
+    let params = AggregationParams {
+        invert: false,
+        quote_rate: None,
+        previous_rate: 0,
+        ..get_aggregation_params(i, symbol, input, None, volumes.as_deref())
+    };
+    let value = aggregate_value(&rates, &weights, &params).ok()?;
+    Some(value.rate as f64 / params.multiplier as f64)
+}
+
+/// Fails the responses of every equivalence group whose rates are implausibly far apart with
+/// `SourceDisagreement`. Groups with a symbol that was not requested or failed are not checked.
+/// The base rates are compared, so inverting a member or quoting the rates does not affect it
+fn cross_check_groups<'a>(
+    mut responses: Vec<(usize, &'a String, Response)>,
+    source_data: &SourceData,
+    input: &Input,
+) -> Vec<(usize, &'a String, Response)> {
+    // Gets the unscaled base rate of each requested canonical symbol that is usable
+    let rates = responses
+        .iter()
+        .filter(|(_, _, response)| {
+            matches!(
+                ResponseCode::from(response.response_code),
+                ResponseCode::Success | ResponseCode::SuccessWithWarning | ResponseCode::Degraded
+            )
+        })
+        .filter_map(|(i, symbol, _)| {
+            let symbol = canonicalize_symbol(symbol)?;
+            Some((symbol, get_base_rate(*i, symbol, source_data, input)?))
+        })
+        .collect::<HashMap<&str, f64>>();
+
+    let implausible_symbols = GROUPS
+        .iter()
+        .filter(
+            |group| match (rates.get(group.symbol), rates.get(group.reference)) {
+                (Some(rate), Some(reference_rate)) if *reference_rate > 0f64 => {
+                    let ratio = rate / reference_rate;
+                    ratio < group.min_ratio || ratio > group.max_ratio
+                }
+                _ => false,
+            },
+        )
+        .flat_map(|group| [group.symbol, group.reference])
+        .collect::<HashSet<&str>>();
+    for (_, symbol, response) in responses.iter_mut() {
+        if canonicalize_symbol(symbol).is_some_and(|symbol| implausible_symbols.contains(symbol)) {
+            response.response_code = ResponseCode::SourceDisagreement as u8;
+            response.rate = 0;
+            response.spread = 0;
+            response.secondary_rate = 0;
+            response.std_dev = 0;
+            response.rate_str.clear();
+        }
+    }
+    responses
+}
+
+/// Gets the oracle script responses, one per requested symbol, or one per symbol and quote
+/// symbol pair when `quote_symbols` is set
+pub fn get_responses(symbols: &[String], source_data: &SourceData, input: &Input) -> Vec<Response> {
+    if input.quote_symbols.is_empty() {
+        let quote_rate = get_quote_rate(&input.quote_symbol, source_data, input);
+        return get_quoted_responses(symbols, source_data, input, quote_rate);
+    }
+
+    // Prices every symbol against each quote symbol in turn, keeping each symbol's pairs together
+    let mut quoted_responses = input
+        .quote_symbols
+        .iter()
+        .map(|quote_symbol| {
+            let quote_rate = get_quote_rate(quote_symbol, source_data, input);
+            get_quoted_responses(symbols, source_data, input, quote_rate)
+                .into_iter()
+                .map(move |mut response| {
+                    response.symbol = format!("{}/{}", response.symbol, quote_symbol);
+                    response
+                })
+        })
+        .collect::<Vec<_>>();
+    let mut responses = Vec::new();
+    while let Some(pairs) = quoted_responses
+        .iter_mut()
+        .map(Iterator::next)
+        .collect::<Option<Vec<Response>>>()
+    {
+        responses.extend(pairs);
+    }
+    responses
+}
+
+/// Gets the responses of the symbols priced in terms of the quote rate, if any
+fn get_quoted_responses(
+    symbols: &[String],
+    source_data: &SourceData,
+    input: &Input,
+    quote_rate: Option<Result<f64, ResponseCode>>,
+) -> Vec<Response> {
+    let responses = symbols
+        .iter()
+        .enumerate()
+        .filter(|(_, symbol)| !is_skipped(symbol, input))
+        .map(|(i, symbol)| {
+            (
+                i,
+                symbol,
+                get_response(i, symbol, source_data, input, quote_rate),
+            )
+        })
+        .collect::<Vec<(usize, &String, Response)>>();
+    let responses = if input.cross_check_group {
+        cross_check_groups(responses, source_data, input)
+    } else {
+        responses
+    };
+    responses
+        .into_iter()
+        .map(|(i, symbol, response)| {
+            if !input.verbose_errors {
+                return response;
+            }
+
+            // Explains failures with the source count the symbol required
+            let required_source_count = get_required_source_count(
+                get_minimum_source_count(
+                    i,
+                    &input.minimum_source_counts,
+                    input.minimum_source_count,
+                    get_source_count(symbol),
+                ),
+                canonicalize_symbol(symbol).map_or(0, get_declared_minimum_source_count),
+            );
+            let code = ResponseCode::from(response.response_code);
+            let reason = get_failure_reason(code, response.source_count, required_source_count);
+            let discarded_rows = canonicalize_symbol(symbol)
+                .and_then(|symbol| source_data.discarded_rows.get(symbol))
+                .copied()
+                .unwrap_or(0);
+            let reason = match discarded_rows {
+                0 => reason,
+                _ if reason.is_empty() => format!("{} malformed rows discarded", discarded_rows),
+                _ => format!("{}, {} malformed rows discarded", reason, discarded_rows),
+            };
+            let response = response.with_reason(reason);
+            if code == ResponseCode::NotEnoughSources {
+                response.with_missing_sources(get_missing_sources(i, symbol, source_data, input))
+            } else {
+                response
+            }
+        })
+        .collect()
+}
+
+/// Gets the ids of the selected data sources of the symbol at the given index that produced no
+/// usable value
+fn get_missing_sources(
+    i: usize,
+    symbol: &str,
+    source_data: &SourceData,
+    input: &Input,
+) -> Vec<u32> {
+    let source_mask = input.source_masks.get(i).copied().unwrap_or(0);
+    let responded = canonicalize_symbol(symbol)
+        .and_then(|canonical| source_data.prices.get(canonical))
+        .map(|prices| {
+            prices
+                .iter()
+                .map(|(ds, _)| *ds)
+                .collect::<Vec<DataSources>>()
+        })
+        .unwrap_or_default();
+    get_sources(symbol)
+        .iter()
+        .filter(|ds| is_source_selected(symbol, **ds, source_mask) && !responded.contains(ds))
+        .map(|ds| *ds as u32)
+        .collect()
+}
+
+/// Gets the high precision responses matching the responses of the requested symbols
+pub fn get_high_precision_responses(
+    symbols: &[String],
+    source_data: &SourceData,
+    input: &Input,
+    responses: &[Response],
+) -> Vec<HighPrecisionResponse> {
+    // Responses come in the order of the symbols, with each symbol's quote pairs together
+    let quote_rates = if input.quote_symbols.is_empty() {
+        vec![get_quote_rate(&input.quote_symbol, source_data, input)]
+    } else {
+        input
+            .quote_symbols
+            .iter()
+            .map(|quote_symbol| get_quote_rate(quote_symbol, source_data, input))
+            .collect()
+    };
+    let pairs = symbols
+        .iter()
+        .enumerate()
+        .filter(|(_, symbol)| !is_skipped(symbol, input))
+        .flat_map(|(i, symbol)| {
+            quote_rates
+                .iter()
+                .map(move |quote_rate| (i, symbol, *quote_rate))
+        });
+    zip(pairs, responses)
+        .map(|((i, symbol, quote_rate), response)| {
+            get_high_precision_response(i, symbol, source_data, input, quote_rate, response)
+        })
+        .collect()
+}
+
+/// Returns the scaled per-source rates of each symbol, in the order the sources were collected.
+/// The rates are always unquoted, and are repeated for each of a symbol's quote pairs so they line
+/// up with the responses
+pub fn get_debug_rates(
+    symbols: &[String],
+    source_data: &SourceData,
+    input: &Input,
+) -> Vec<Vec<u64>> {
+    let pair_count = input.quote_symbols.len().max(1);
+    symbols
+        .iter()
+        .filter(|symbol| !is_skipped(symbol, input))
+        .flat_map(|symbol| {
+            let symbol = canonicalize_symbol(symbol).unwrap_or(symbol);
+            let params = AggregationParams {
+                multiplier: get_multiplier(symbol),
+                ..Default::default()
+            };
+            let rates = source_data
+                .prices
+                .get(symbol)
+                .map(|prices| {
+                    prices
+                        .iter()
+                        .map(|(_, rate)| finalize_rate(*rate, &params).unwrap_or(0))
+                        .collect::<Vec<u64>>()
+                })
+                .unwrap_or_default();
+            vec![rates; pair_count]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregation_method_code() {
+        // Test valid code case
+        assert_eq!(
+            AggregationMethodCode::try_from(0),
+            Ok(AggregationMethodCode(0))
+        );
+        assert_eq!(
+            AggregationMethodCode::try_from(4),
+            Ok(AggregationMethodCode(4))
+        );
+        assert_eq!(
+            AggregationMethodCode::try_from(5),
+            Ok(AggregationMethodCode(5))
+        );
+        assert_eq!(
+            AggregationMethodCode::try_from(6),
+            Ok(AggregationMethodCode(6))
+        );
+        assert_eq!(
+            AggregationMethodCode::try_from(7),
+            Ok(AggregationMethodCode(7))
+        );
+
+        // Test out of range code case
+        assert_eq!(
+            AggregationMethodCode::try_from(8),
+            Err(ResponseCode::UnsupportedAggregationMethod)
+        );
+
+        // Test schema case
+        assert_eq!(
+            AggregationMethodCode::declaration(),
+            "AggregationMethodCode"
+        );
+        let mut definitions = HashMap::new();
+        AggregationMethodCode::add_definitions_recursively(&mut definitions);
+        assert_eq!(
+            obi::get_schema(AggregationMethodCode::declaration(), &definitions),
+            "{code:u8}"
+        );
+        assert_eq!(
+            AggregationMethodCode::try_from_slice(&[4]).unwrap(),
+            AggregationMethodCode(4)
+        );
+        assert_eq!(
+            Vec::<AggregationMethodCode>::try_from_slice(&[0, 0, 0, 2, 0, 3]).unwrap(),
+            vec![AggregationMethodCode(0), AggregationMethodCode(3)]
+        );
+    }
+
+    #[test]
+    fn test_rounding_mode_code() {
+        // Test valid code case
+        assert_eq!(RoundingModeCode::try_from(0), Ok(RoundingModeCode(0)));
+        assert_eq!(RoundingModeCode::try_from(2), Ok(RoundingModeCode(2)));
+
+        // Test out of range code case
+        assert_eq!(
+            RoundingModeCode::try_from(3),
+            Err(ResponseCode::UnsupportedRoundingMode)
+        );
+
+        // Test schema case
+        assert_eq!(RoundingModeCode::declaration(), "RoundingModeCode");
+        let mut definitions = HashMap::new();
+        RoundingModeCode::add_definitions_recursively(&mut definitions);
+        assert_eq!(
+            obi::get_schema(RoundingModeCode::declaration(), &definitions),
+            "{code:u8}"
+        );
+    }
+
+    #[test]
+    fn test_filter_and_medianize() {
+        // Test normal case
+        let rates = vec![
+            vec![Some(0.0), Some(1.3), Some(2.3)],
+            vec![Some(0.1), Some(1.0), Some(2.0)],
+            vec![Some(0.3), Some(1.1), Some(2.3)],
+            vec![Some(0.3), Some(1.1), Some(2.3)],
+        ];
+        let result =
+            filter_and_medianize(rates, 3, 2, 4, 0, EvenRule::Average, MissingPolicy::Drop);
+        let expected_result = vec![Some(0.2), Some(1.1), Some(2.3)];
+        assert_eq!(result, expected_result);
+
+        // Test too many missing case
+        let rates = vec![
+            vec![Some(0.0), Some(1.3), None],
+            vec![Some(0.1), Some(1.0), None],
+            vec![Some(0.3), Some(1.1), None],
+            vec![Some(0.3), Some(1.1), Some(2.3)],
+        ];
+        let result =
+            filter_and_medianize(rates, 3, 2, 4, 0, EvenRule::Average, MissingPolicy::Drop);
+        let expected_result = vec![Some(0.2), Some(1.1), None];
+        assert_eq!(result, expected_result);
+
+        // Test outlier rejection case
+        let rates = vec![
+            vec![Some(1.0)],
+            vec![Some(1.1)],
+            vec![Some(1.2)],
+            vec![Some(50.0)],
+        ];
+        let result = filter_and_medianize(
+            rates.clone(),
+            1,
+            2,
+            4,
+            0,
+            EvenRule::Average,
+            MissingPolicy::Drop,
+        );
+        assert_eq!(result, vec![Some(1.15)]);
+        let result =
+            filter_and_medianize(rates, 1, 2, 4, 3, EvenRule::Average, MissingPolicy::Drop);
+        assert_eq!(result, vec![Some(1.1)]);
+
+        // Test fewer responses than minimum case
+        let rates = vec![vec![Some(1.5)], vec![Some(1.5)]];
+        let result = filter_and_medianize(
+            rates.clone(),
+            1,
+            3,
+            2,
+            0,
+            EvenRule::Average,
+            MissingPolicy::Drop,
+        );
+        assert_eq!(result, vec![Some(1.5)]);
+        let result =
+            filter_and_medianize(rates, 1, 3, 3, 0, EvenRule::Average, MissingPolicy::Drop);
+        assert_eq!(result, vec![None]);
+
+        // Test even rule case
+        let rates = vec![
+            vec![Some(1.0)],
+            vec![Some(1.2)],
+            vec![Some(1.1)],
+            vec![Some(1.3)],
+        ];
+        let result = filter_and_medianize(
+            rates.clone(),
+            1,
+            2,
+            4,
+            0,
+            EvenRule::Lower,
+            MissingPolicy::Drop,
+        );
+        assert_eq!(result, vec![Some(1.1)]);
+        let result = filter_and_medianize(rates, 1, 2, 4, 0, EvenRule::Upper, MissingPolicy::Drop);
+        assert_eq!(result, vec![Some(1.2)]);
+
+        // Test no responses case
+        let result =
+            filter_and_medianize(vec![], 1, 3, 0, 0, EvenRule::Average, MissingPolicy::Drop);
+        assert_eq!(result, vec![None]);
+
+        // Test ragged outputs case
+        let rates = vec![
+            vec![Some(1.0), Some(2.0)],
+            vec![Some(1.2)],
+            vec![],
+            vec![Some(1.1), Some(2.2), Some(3.0)],
+        ];
+        let result =
+            filter_and_medianize(rates, 2, 2, 4, 0, EvenRule::Average, MissingPolicy::Drop);
+        assert_eq!(result, vec![Some(1.1), Some(2.1)]);
+
+        // Test previous value policy case
+        let rates = vec![
+            vec![Some(1.0), None, None],
+            vec![Some(1.2), None, None],
+            vec![Some(1.1), Some(2.1), None],
+        ];
+        let result = filter_and_medianize(
+            rates.clone(),
+            3,
+            2,
+            3,
+            0,
+            EvenRule::Average,
+            MissingPolicy::Drop,
+        );
+        assert_eq!(result, vec![Some(1.1), None, None]);
+        let previous = [Some(0.9), Some(2.0), None];
+        let result = filter_and_medianize(
+            rates.clone(),
+            3,
+            2,
+            3,
+            0,
+            EvenRule::Average,
+            MissingPolicy::PreviousValue(&previous),
+        );
+        assert_eq!(result, vec![Some(1.1), Some(2.0), None]);
+        let result = filter_and_medianize(
+            rates,
+            3,
+            2,
+            3,
+            0,
+            EvenRule::Average,
+            MissingPolicy::PreviousValue(&[Some(0.9)]),
+        );
+        assert_eq!(result, vec![Some(1.1), None, None]);
+    }
+
+    #[test]
+    fn test_median_by_rule() {
+        // Test average case
+        let result = median_by_rule(&mut [4.0, 1.0, 3.0, 2.0], EvenRule::Average);
+        assert_eq!(result, Some(2.5));
+
+        // Test lower case
+        let result = median_by_rule(&mut [4.0, 1.0, 3.0, 2.0], EvenRule::Lower);
+        assert_eq!(result, Some(2.0));
+
+        // Test upper case
+        let result = median_by_rule(&mut [4.0, 1.0, 3.0, 2.0], EvenRule::Upper);
+        assert_eq!(result, Some(3.0));
+
+        // Test odd case ignoring the rule
+        let result = median_by_rule(&mut [1.3, 1.1, 1.2], EvenRule::Lower);
+        assert_eq!(result, Some(1.2));
+
+        // Test empty case
+        let result = median_by_rule(&mut [], EvenRule::Upper);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_even_rule() {
+        // Test supported case
+        assert_eq!(EvenRule::try_from(0), Ok(EvenRule::Average));
+        assert_eq!(EvenRule::try_from(2), Ok(EvenRule::Upper));
+
+        // Test unsupported case
+        assert_eq!(
+            EvenRule::try_from(3),
+            Err(ResponseCode::UnsupportedEvenRule)
+        );
+    }
+
+    #[test]
+    fn test_even_rule_code() {
+        // Test valid code case
+        assert_eq!(EvenRuleCode::try_from(1), Ok(EvenRuleCode(1)));
+
+        // Test out of range code case
+        assert_eq!(
+            EvenRuleCode::try_from(3),
+            Err(ResponseCode::UnsupportedEvenRule)
+        );
+
+        // Test schema case
+        assert_eq!(EvenRuleCode::declaration(), "EvenRuleCode");
+        let mut definitions = HashMap::new();
+        EvenRuleCode::add_definitions_recursively(&mut definitions);
+        assert_eq!(
+            obi::get_schema(EvenRuleCode::declaration(), &definitions),
+            "{code:u8}"
+        );
+    }
+
+    #[test]
+    fn test_median() {
+        // Test odd case
+        let result = median(&mut [1.3, 1.1, 1.2]);
+        assert_eq!(result, Some(1.2));
+
+        // Test even case
+        let result = median(&mut [2.0, 1.0]);
+        assert_eq!(result, Some(1.5));
+        let result = median(&mut [4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(result, Some(2.5));
+
+        // Test single case
+        let result = median(&mut [1.0]);
+        assert_eq!(result, Some(1.0));
+
+        // Test empty case
+        let result = median(&mut []);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_reject_outliers() {
+        // Test clear outlier case
+        let rates = vec![1.0, 1.1, 1.2, 1.1, 50.0];
+        let result = reject_outliers(rates.clone(), 3);
+        assert_eq!(result, vec![1.0, 1.1, 1.2, 1.1]);
+
+        // Test disabled case
+        let result = reject_outliers(rates, 0);
+        assert_eq!(result, vec![1.0, 1.1, 1.2, 1.1, 50.0]);
+
+        // Test zero MAD case
+        let rates = vec![2.0, 2.0, 2.0];
+        let result = reject_outliers(rates, 3);
+        assert_eq!(result, vec![2.0, 2.0, 2.0]);
+
+        // Test empty case
+        let result = reject_outliers(vec![], 3);
+        assert_eq!(result, Vec::<f64>::new());
+    }
+
+    #[test]
+    fn test_weighted_median() {
+        // Test equal weights odd case
+        let rates = vec![1.25, 1.23, 1.27, 1.24, 1.26];
+        let result = weighted_median(&rates, &[1; 5]);
+        assert_eq!(result, Some(1.25));
+
+        // Test equal weights even case
+        let rates = vec![1.0, 2.0];
+        let result = weighted_median(&rates, &[1, 1]);
+        assert_eq!(result, Some(1.5));
+
+        // Test 3:1 weight case
+        let rates = vec![1.0, 2.0];
+        let result = weighted_median(&rates, &[1, 3]);
+        assert_eq!(result, Some(2.0));
+        let result = weighted_median(&rates, &[3, 1]);
+        assert_eq!(result, Some(1.0));
+
+        // Test empty case
+        let result = weighted_median(&[], &[]);
+        assert_eq!(result, None);
+
+        // Test zero weight case
+        let result = weighted_median(&[1.0], &[0]);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_volume_weighted_mean() {
+        // Test weighted case
+        let result = volume_weighted_mean(&[1.0, 2.0], &[100.0, 300.0]);
+        assert_eq!(result, Some(1.75));
+
+        // Test zero volume case
+        let result = volume_weighted_mean(&[1.0, 2.0], &[0.0, 0.0]);
+        assert_eq!(result, None);
+
+        // Test mismatched length case
+        let result = volume_weighted_mean(&[1.0, 2.0], &[100.0]);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_get_volumes() {
+        let prices = vec![
+            (DataSources::DS1INCHETH, 1.0),
+            (DataSources::DSARKENETH, 2.0),
+        ];
+
+        // Test all volumes case
+        let volumes = vec![
+            (DataSources::DSARKENETH, 300.0),
+            (DataSources::DS1INCHETH, 100.0),
+        ];
+        assert_eq!(
+            get_volumes(&prices, Some(&volumes)),
+            Some(vec![100.0, 300.0])
+        );
+
+        // Test missing volume case
+        let volumes = vec![(DataSources::DS1INCHETH, 100.0)];
+        assert_eq!(get_volumes(&prices, Some(&volumes)), None);
+
+        // Test no volumes case
+        assert_eq!(get_volumes(&prices, None), None);
+    }
+
+    #[test]
+    fn test_trimmed_mean() {
+        // Test outlier case
+        let result = trimmed_mean(&[1.0, 2.0, 3.0, 100.0]);
+        assert_eq!(result, Some(2.5));
+
+        // Test two rates case
+        let result = trimmed_mean(&[1.0, 2.0]);
+        assert_eq!(result, Some(1.5));
+
+        // Test empty case
+        let result = trimmed_mean(&[]);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_winsorized_median() {
+        // Test outlier case
+        let rates = [1.0, 1.1, 1.2, 100.0];
+        assert_eq!(winsorized_median(&rates, &[1; 4]), Some(1.15));
+        assert_eq!(weighted_median(&rates, &[1; 4]), Some(1.15));
+
+        // Test heavily weighted outlier case
+        let weights = [1, 1, 1, 10];
+        assert_eq!(winsorized_median(&rates, &weights), Some(1.2));
+        assert_eq!(weighted_median(&rates, &weights), Some(100.0));
+        let rates = [0.001, 1.0, 1.1, 1.2, 1.3];
+        let weights = [10, 1, 1, 1, 1];
+        assert_eq!(winsorized_median(&rates, &weights), Some(1.0));
+        assert_eq!(weighted_median(&rates, &weights), Some(0.001));
+
+        // Test small source count case
+        let rates = [1.0, 1.1, 100.0];
+        let weights = [1, 1, 10];
+        assert_eq!(winsorized_median(&rates, &weights), Some(100.0));
+        assert_eq!(winsorized_median(&[1.0, 100.0], &[1; 2]), Some(50.5));
+
+        // Test empty case
+        assert_eq!(winsorized_median(&[], &[]), None);
+    }
+
+    #[test]
+    fn test_mode() {
+        // Test most common rate case
+        assert_eq!(mode(&[1.0, 1.0, 1.0, 2.0]), Some(1.0));
+        assert_eq!(mode(&[2.0, 1.0, 3.0, 2.0, 1.0, 2.0]), Some(2.0));
+
+        // Test tie nearest the median case
+        assert_eq!(mode(&[1.0, 1.0, 2.0, 2.0, 2.5]), Some(2.0));
+        assert_eq!(mode(&[1.0, 1.0, 3.0, 3.0, 0.5]), Some(1.0));
+
+        // Test equidistant tie case
+        assert_eq!(mode(&[1.0, 3.0]), Some(1.0));
+
+        // Test tolerance case
+        assert_eq!(mode(&[1.0, 1.00005, 2.0]), Some(1.000025));
+        assert_eq!(mode(&[1.0, 1.0002, 1.0002]), Some(1.0002));
+
+        // Test empty case
+        assert_eq!(mode(&[]), None);
+    }
+
+    #[test]
+    fn test_geometric_mean() {
+        // Test normal case
+        let result = geometric_mean(&[1.0, 4.0]);
+        assert_eq!(result, Some(2.0));
+
+        // Test single rate case
+        let result = geometric_mean(&[1.25]);
+        assert_eq!(result, Some(1.25));
+
+        // Test empty case
+        let result = geometric_mean(&[]);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_harmonic_mean() {
+        // Test normal case
+        let result = harmonic_mean(&[1.0, 2.0]);
+        assert_eq!(result, Some(4.0 / 3.0));
+
+        // Test single rate case
+        let result = harmonic_mean(&[1.25]);
+        assert_eq!(result, Some(1.25));
+
+        // Test empty case
+        let result = harmonic_mean(&[]);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_get_spread() {
+        // Test tight cluster case
+        let spread = get_spread(&[1.00, 1.01], 1.005);
+        assert_eq!(spread, 9950248);
+
+        // Test wide cluster case
+        let spread = get_spread(&[1.0, 2.0], 1.5);
+        assert_eq!(spread, 666666666);
+
+        // Test single source case
+        let spread = get_spread(&[1.0], 1.0);
+        assert_eq!(spread, 0);
+
+        // Test identical case
+        let spread = get_spread(&[1.0, 1.0], 1.0);
+        assert_eq!(spread, 0);
+    }
+
+    #[test]
+    fn test_get_std_dev() {
+        let params = AggregationParams::default();
+
+        // Test known set case
+        let std_dev = get_std_dev(&[1.0, 2.0, 3.0], &params);
+        assert_eq!(std_dev, 1000000000);
+
+        // Test identical case
+        let std_dev = get_std_dev(&[2.0, 2.0, 2.0], &params);
+        assert_eq!(std_dev, 0);
+
+        // Test single source case
+        let std_dev = get_std_dev(&[1.0], &params);
+        assert_eq!(std_dev, 0);
+
+        // Test empty case
+        let std_dev = get_std_dev(&[], &params);
+        assert_eq!(std_dev, 0);
+
+        // Test symbol multiplier case
+        let params_wbtc = AggregationParams {
+            multiplier: get_multiplier("WBTC"),
+            ..params
+        };
+        let std_dev = get_std_dev(&[1.0, 2.0, 3.0], &params_wbtc);
+        assert_eq!(std_dev, 1000000);
+
+        // Test quoted case
+        let params_quoted = AggregationParams {
+            quote_rate: Some(2.0),
+            ..params
+        };
+        let std_dev = get_std_dev(&[2.0, 4.0, 6.0], &params_quoted);
+        assert_eq!(std_dev, 1000000000);
+
+        // Test inverted case
+        let params_inverted = AggregationParams {
+            invert: true,
+            ..params
+        };
+        let std_dev = get_std_dev(&[0.5, 1.0, 1.0 / 3.0], &params_inverted);
+        assert_eq!(std_dev, 1000000000);
+
+        // Test unconvertible rate case
+        let std_dev = get_std_dev(&[0.0, 1.0], &params_inverted);
+        assert_eq!(std_dev, 0);
+    }
+
+    #[test]
+    fn test_exceeds_deviation() {
+        // Test within tolerance case
+        assert!(!exceeds_deviation(&[1.0, 1.005], 100));
+
+        // Test outside tolerance case
+        assert!(exceeds_deviation(&[1.0, 1.02], 100));
+
+        // Test disabled case
+        assert!(!exceeds_deviation(&[1.0, 100.0], 0));
+
+        // Test single rate case
+        assert!(!exceeds_deviation(&[1.0], 1));
+
+        // Test zero rate case
+        assert!(exceeds_deviation(&[0.0, 1.0], 10000));
+    }
+
+    /// Parses a decimal string rate back into an integer scaled by the power of ten multiplier
+    fn parse_rate(rate_str: &str, multiplier: u64) -> Option<u64> {
+        scale_decimal(rate_str.parse().ok()?, multiplier, RoundingMode::Truncate)
+            .and_then(|rate| u64::try_from(rate).ok())
+    }
+
+    #[test]
+    fn test_aggregate_values() {
+        let params = AggregationParams {
+            minimum_source_count: 2,
+            ..Default::default()
+        };
+        let rates = [1.0, 1.1, 1.6];
+
+        // Test median and mean case
+        let methods = [
+            AggregationMethodCode(AggregationMethod::Median as u8),
+            AggregationMethodCode(AggregationMethod::Mean as u8),
+        ];
+        assert_eq!(
+            aggregate_values(&rates, &[1; 3], &params, &methods),
+            vec![1100000000, 1233333333]
+        );
+
+        // Test failed method case
+        let methods = [
+            AggregationMethodCode(AggregationMethod::TrimmedMean as u8),
+            AggregationMethodCode(255),
+        ];
+        assert_eq!(
+            aggregate_values(&rates, &[1; 3], &params, &methods),
+            vec![1100000000, 0]
+        );
+
+        // Test no methods case
+        assert!(aggregate_values(&rates, &[1; 3], &params, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_finalize_rate() {
+        let params = AggregationParams::default();
+
+        // Test finite case
+        assert_eq!(finalize_rate(1.5, &params), Ok(1500000000));
+        let params_nearest = AggregationParams {
+            rounding: RoundingMode::Nearest as u8,
+            ..Default::default()
+        };
+        assert_eq!(finalize_rate(1.0000000015, &params_nearest), Ok(1000000002));
+
+        // Test infinite case
+        assert_eq!(
+            finalize_rate(f64::INFINITY, &params),
+            Err(ResponseCode::InvalidNumber)
+        );
+        let params_inverted = AggregationParams {
+            invert: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            finalize_rate(1e-320, &params_inverted),
+            Err(ResponseCode::InvalidNumber)
+        );
+        let params_quoted = AggregationParams {
+            quote_rate: Some(1e-320),
+            ..Default::default()
+        };
+        assert_eq!(
+            finalize_rate(1e10, &params_quoted),
+            Err(ResponseCode::InvalidNumber)
+        );
+
+        // Test NaN case
+        assert_eq!(
+            finalize_rate(f64::NAN, &params),
+            Err(ResponseCode::InvalidNumber)
+        );
+
+        // Test overflow case
+        assert_eq!(finalize_rate(1e11, &params), Err(ResponseCode::Overflow));
+        assert_eq!(finalize_rate(1e300, &params), Err(ResponseCode::Overflow));
+
+        // Test negative case
+        assert_eq!(
+            finalize_rate(-1.0, &params),
+            Err(ResponseCode::NegativeRate)
+        );
+        assert_eq!(
+            finalize_rate(-1e300, &params),
+            Err(ResponseCode::NegativeRate)
+        );
+        assert_eq!(
+            finalize_rate(-2.0, &params_inverted),
+            Err(ResponseCode::NegativeRate)
+        );
+        let params_negative_quote = AggregationParams {
+            quote_rate: Some(-2.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            finalize_rate(1.0, &params_negative_quote),
+            Err(ResponseCode::NegativeRate)
+        );
+        assert_eq!(finalize_rate(-0.0, &params), Ok(0));
+    }
+
+    #[test]
+    fn test_format_rate() {
+        // Test regular multiplier case
+        assert_eq!(format_rate(1234000000, MULTIPLIER), "1.234000000");
+        assert_eq!(format_rate(5, MULTIPLIER), "0.000000005");
+        assert_eq!(format_rate(0, MULTIPLIER), "0.000000000");
+
+        // Test symbol decimals case
+        assert_eq!(format_rate(123456, 10u64.pow(6)), "0.123456");
+        assert_eq!(format_rate(42, 1), "42");
+
+        // Test round trip case
+        for rate in [1234000000, 1, 18446744073709500000] {
+            assert_eq!(
+                parse_rate(&format_rate(rate, MULTIPLIER), MULTIPLIER),
+                Some(rate)
+            );
+        }
+        let rate = 123456789012345678;
+        let multiplier = 10u64.pow(18);
+        assert_eq!(format_rate(rate, multiplier), "0.123456789012345678");
+    }
+
+    #[test]
+    fn test_scale_decimal() {
+        // Test exact scaling case
+        let scaled_rate = scale_decimal(1.1, MULTIPLIER, RoundingMode::Truncate);
+        assert_eq!(scaled_rate, Some(1100000000));
+        let scaled_rate = scale_decimal(0.3, 10u64.pow(18), RoundingMode::Truncate);
+        assert_eq!(scaled_rate, Some(300000000000000000));
+        let scaled_rate = scale_decimal(0.0, MULTIPLIER, RoundingMode::Ceil);
+        assert_eq!(scaled_rate, Some(0));
+
+        // Test u64 boundary case
+        let scaled_rate = scale_decimal(18446744073.70955, MULTIPLIER, RoundingMode::Truncate);
+        assert_eq!(scaled_rate, Some(18446744073709550000));
+        assert!(scaled_rate.unwrap() <= u64::MAX as u128);
+
+        // Test rounding case
+        let scaled_rate = scale_decimal(1.0000000015, MULTIPLIER, RoundingMode::Truncate);
+        assert_eq!(scaled_rate, Some(1000000001));
+        let scaled_rate = scale_decimal(1.0000000015, MULTIPLIER, RoundingMode::Nearest);
+        assert_eq!(scaled_rate, Some(1000000002));
+        let scaled_rate = scale_decimal(1.0000000011, MULTIPLIER, RoundingMode::Nearest);
+        assert_eq!(scaled_rate, Some(1000000001));
+        let scaled_rate = scale_decimal(1.0000000011, MULTIPLIER, RoundingMode::Ceil);
+        assert_eq!(scaled_rate, Some(1000000002));
+
+        // Test unsupported case
+        let scaled_rate = scale_decimal(1.5, 3, RoundingMode::Truncate);
+        assert_eq!(scaled_rate, None);
+        let scaled_rate = scale_decimal(1e300, MULTIPLIER, RoundingMode::Truncate);
+        assert_eq!(scaled_rate, None);
+        let scaled_rate = scale_decimal(-1.5, MULTIPLIER, RoundingMode::Truncate);
+        assert_eq!(scaled_rate, None);
+    }
+
+    #[test]
+    fn test_aggregate_value() {
+        let params = AggregationParams {
+            minimum_source_count: 3,
+            ..Default::default()
+        };
+
+        // Test normal case
+        let data = vec![1.23, 1.24, 1.25, 1.26, 1.27];
+        let normal_res = aggregate_value(&data, &[1; 5], &params);
+        assert_eq!(normal_res.unwrap().rate, 1250000000);
+
+        // Test warning case
+        let warning_params = AggregationParams {
+            report_warnings: true,
+            ..params
+        };
+        let at_minimum_res = aggregate_value(&data[..3], &[1; 3], &warning_params).unwrap();
+        assert!(at_minimum_res.warning);
+        assert_eq!(at_minimum_res.rate, 1240000000);
+        let above_minimum_res = aggregate_value(&data, &[1; 5], &warning_params).unwrap();
+        assert!(!above_minimum_res.warning);
+        let spread_data = vec![1.0, 1.2, 1.1, 1.15];
+        let spread_res = aggregate_value(&spread_data, &[1; 4], &warning_params).unwrap();
+        assert!(spread_res.warning);
+        let unflagged_res = aggregate_value(&data[..3], &[1; 3], &params).unwrap();
+        assert!(!unflagged_res.warning);
+
+        // Test u64 boundary case, where the rate just under the limit converts exactly
+        let boundary_data = vec![18446744073.7095; 3];
+        let boundary_res = aggregate_value(&boundary_data, &[1; 3], &params);
+        assert_eq!(boundary_res.unwrap().rate, 18446744073709500000);
+        let boundary_data = vec![18446744073.7096; 3];
+        let boundary_res = aggregate_value(&boundary_data, &[1; 3], &params);
+        assert_eq!(boundary_res.unwrap_err(), ResponseCode::Overflow);
+
+        // Test source deviation case
+        let within_res = aggregate_value(
+            &data,
+            &[1; 5],
+            &AggregationParams {
+                max_deviation_bps: 500,
+                ..params
+            },
+        );
+        assert_eq!(within_res.unwrap().rate, 1250000000);
+        let outside_res = aggregate_value(
+            &data,
+            &[1; 5],
+            &AggregationParams {
+                max_deviation_bps: 100,
+                ..params
+            },
+        );
+        assert_eq!(outside_res.unwrap_err(), ResponseCode::SourceDisagreement);
+
+        // Test quote rate case
+        let quote_res = aggregate_value(
+            &data,
+            &[1; 5],
+            &AggregationParams {
+                quote_rate: Some(2.0),
+                ..params
+            },
+        );
+        assert_eq!(quote_res.unwrap().rate, 625000000);
+        let zero_quote_res = aggregate_value(
+            &data,
+            &[1; 5],
+            &AggregationParams {
+                quote_rate: Some(0.0),
+                ..params
+            },
+        );
+        assert_eq!(zero_quote_res.unwrap_err(), ResponseCode::ConversionError);
+
+        // Test rounding mode case
+        let data = vec![1.9999999995];
+        let rounding_params = AggregationParams {
+            minimum_source_count: 1,
+            ..params
+        };
+        let truncated_res = aggregate_value(&data, &[1], &rounding_params);
+        assert_eq!(truncated_res.unwrap().rate, 1999999999);
+        let nearest_res = aggregate_value(
+            &data,
+            &[1],
+            &AggregationParams {
+                rounding: 1,
+                ..rounding_params
+            },
+        );
+        assert_eq!(nearest_res.unwrap().rate, 2000000000);
+        let ceil_res = aggregate_value(
+            &data,
+            &[1],
+            &AggregationParams {
+                rounding: 2,
+                ..rounding_params
+            },
+        );
+        assert_eq!(ceil_res.unwrap().rate, 2000000000);
+        let nearest_res = aggregate_value(
+            &[1.9999999994],
+            &[1],
+            &AggregationParams {
+                rounding: 1,
+                ..rounding_params
+            },
+        );
+        assert_eq!(nearest_res.unwrap().rate, 1999999999);
+        let unsupported_res = aggregate_value(
+            &data,
+            &[1],
+            &AggregationParams {
+                rounding: 3,
+                ..rounding_params
+            },
+        );
+        assert_eq!(
+            unsupported_res.unwrap_err(),
+            ResponseCode::UnsupportedRoundingMode
+        );
+
+        // Test rounding overflow case
+        let overflow_res = aggregate_value(
+            &[1e11],
+            &[1],
+            &AggregationParams {
+                rounding: 2,
+                ..rounding_params
+            },
+        );
+        assert_eq!(overflow_res.unwrap_err(), ResponseCode::Overflow);
+
+        // Test volume weighted mean case
+        let data = vec![1.0, 2.0];
+        let vwap_params = AggregationParams {
+            minimum_source_count: 2,
+            aggregation_method: 4,
+            ..params
+        };
+        let vwap_res = aggregate_value(
+            &data,
+            &[1; 2],
+            &AggregationParams {
+                volumes: Some(&[100.0, 300.0]),
+                ..vwap_params
+            },
+        );
+        assert_eq!(vwap_res.unwrap().rate, 1750000000);
+
+        // Test volume weighted mean without volumes falls back to median case
+        let fallback_res = aggregate_value(&data, &[1; 2], &vwap_params);
+        assert_eq!(fallback_res.unwrap().rate, 1500000000);
+
+        // Test zero minimum source count case
+        let zero_params = AggregationParams {
+            minimum_source_count: 0,
+            ..params
+        };
+        let empty_res = aggregate_value(&[], &[], &zero_params);
+        assert_eq!(empty_res.unwrap_err(), ResponseCode::NotEnoughSources);
+        let single_res = aggregate_value(&[1.5], &[1], &zero_params);
+        assert_eq!(single_res.unwrap().rate, 1500000000);
+
+        // Test overflow case
+        let invalid_data = vec![f64::MAX, f64::MAX, f64::MAX, f64::MAX, f64::MAX];
+        let overflow_res = aggregate_value(&invalid_data, &[1; 5], &params);
+        assert_eq!(overflow_res.unwrap_err(), ResponseCode::Overflow);
+
+        // Test underflow case
+        let invalid_data = vec![f64::MIN, f64::MIN, f64::MIN, f64::MIN, f64::MIN];
+        let overflow_res = aggregate_value(&invalid_data, &[1; 5], &params);
+        assert_eq!(overflow_res.unwrap_err(), ResponseCode::NegativeRate);
+
+        // Test NaN case
+        let invalid_data = vec![f64::NAN, f64::NAN, f64::NAN, f64::NAN, f64::NAN];
+        let overflow_res = aggregate_value(&invalid_data, &[1; 5], &params);
+        assert_eq!(overflow_res.unwrap_err(), ResponseCode::InvalidNumber);
+
+        // Test spread case
+        let data = vec![1.0, 2.0];
+        let spread_res = aggregate_value(
+            &data,
+            &[1; 2],
+            &AggregationParams {
+                minimum_source_count: 2,
+                ..params
+            },
+        );
+        assert_eq!(
+            spread_res.unwrap(),
+            AggregatedValue {
+                rate: 1500000000,
+                spread: 666666666,
+                secondary_rate: 1500000000,
+                std_dev: 707106781,
+                degraded: false,
+                warning: false
+            }
+        );
+
+        // Test weighted case
+        let data = vec![1.0, 2.0, 2.0, 1.0];
+        let weighted_res = aggregate_value(&data, &[3, 1, 1, 3], &params);
+        assert_eq!(weighted_res.unwrap().rate, 1000000000);
+
+        // Test mean fallback case, where zero weights leave no median
+        let data = vec![1.0, 2.0, 6.0];
+        assert_eq!(weighted_median(&data, &[0; 3]), None);
+        let fallback_res = aggregate_value(&data, &[0; 3], &params);
+        assert_eq!(fallback_res.unwrap().rate, 3000000000);
+
+        // Test non-finite fallback case
+        let data = vec![1.0, f64::NAN, 6.0];
+        let fallback_res = aggregate_value(&data, &[0; 3], &params);
+        assert_eq!(fallback_res.unwrap_err(), ResponseCode::InvalidNumber);
+        let data = vec![1.0, f64::INFINITY, 6.0];
+        let fallback_res = aggregate_value(&data, &[0; 3], &params);
+        assert_eq!(fallback_res.unwrap_err(), ResponseCode::InvalidNumber);
+
+        // Test trimmed mean case
+        let data = vec![1.0, 2.0, 3.0, 100.0];
+        let median_res = aggregate_value(&data, &[1; 4], &params);
+        assert_eq!(median_res.unwrap().rate, 2500000000);
+        let trimmed_res = aggregate_value(
+            &data,
+            &[1; 4],
+            &AggregationParams {
+                aggregation_method: 1,
+                ..params
+            },
+        );
+        assert_eq!(trimmed_res.unwrap().rate, 2500000000);
+        let data = vec![1.0, 2.0, 3.0, 7.0, 100.0];
+        let median_res = aggregate_value(&data, &[1; 5], &params);
+        assert_eq!(median_res.unwrap().rate, 3000000000);
+        let trimmed_res = aggregate_value(
+            &data,
+            &[1; 5],
+            &AggregationParams {
+                aggregation_method: 1,
+                ..params
+            },
+        );
+        assert_eq!(trimmed_res.unwrap().rate, 4000000000);
+
+        // Test winsorized median case
+        let data = vec![1.0, 2.0, 3.0, 100.0];
+        let median_res = aggregate_value(&data, &[1, 1, 1, 5], &params);
+        assert_eq!(median_res.unwrap().rate, 100000000000);
+        let winsorized_res = aggregate_value(
+            &data,
+            &[1, 1, 1, 5],
+            &AggregationParams {
+                aggregation_method: 6,
+                ..params
+            },
+        );
+        assert_eq!(winsorized_res.unwrap().rate, 3000000000);
+
+        // Test mode case
+        let data = vec![1.0, 1.0, 1.0, 2.0];
+        let mode_res = aggregate_value(
+            &data,
+            &[1; 4],
+            &AggregationParams {
+                aggregation_method: 7,
+                ..params
+            },
+        );
+        assert_eq!(mode_res.unwrap().rate, 1000000000);
+
+        // Test geometric mean case
+        let data = vec![1.0, 4.0];
+        let geometric_res = aggregate_value(
+            &data,
+            &[1; 2],
+            &AggregationParams {
+                minimum_source_count: 2,
+                aggregation_method: 2,
+                ..params
+            },
+        );
+        assert_eq!(geometric_res.unwrap().rate, 2000000000);
+
+        // Test geometric mean with zero case
+        let data = vec![0.0, 4.0];
+        let geometric_res = aggregate_value(
+            &data,
+            &[1; 2],
+            &AggregationParams {
+                minimum_source_count: 2,
+                aggregation_method: 2,
+                ..params
+            },
+        );
+        assert_eq!(geometric_res.unwrap_err(), ResponseCode::ConversionError);
+
+        // Test harmonic mean case
+        let data = vec![1.0, 2.0];
+        let harmonic_res = aggregate_value(
+            &data,
+            &[1; 2],
+            &AggregationParams {
+                minimum_source_count: 2,
+                aggregation_method: 5,
+                ..params
+            },
+        );
+        assert_eq!(harmonic_res.unwrap().rate, 1333333333);
+
+        // Test harmonic mean with zero case
+        let data = vec![0.0, 2.0];
+        let harmonic_res = aggregate_value(
+            &data,
+            &[1; 2],
+            &AggregationParams {
+                minimum_source_count: 2,
+                aggregation_method: 5,
+                ..params
+            },
+        );
+        assert_eq!(harmonic_res.unwrap_err(), ResponseCode::ConversionError);
+
+        // Test mean case
+        let data = vec![1.0, 2.0, 3.0];
+        let mean_res = aggregate_value(
+            &data,
+            &[1; 3],
+            &AggregationParams {
+                aggregation_method: 3,
+                ..params
+            },
+        );
+        assert_eq!(mean_res.unwrap().rate, 2000000000);
+
+        // Test mean overflow case
+        let data = vec![1e10, 2e10, 3e10];
+        let mean_res = aggregate_value(
+            &data,
+            &[1; 3],
+            &AggregationParams {
+                aggregation_method: 3,
+                ..params
+            },
+        );
+        assert_eq!(mean_res.unwrap_err(), ResponseCode::Overflow);
+
+        // Test unsupported aggregation method case
+        let data = vec![1.23, 1.24, 1.25];
+        let unsupported_res = aggregate_value(
+            &data,
+            &[1; 3],
+            &AggregationParams {
+                aggregation_method: 255,
+                ..params
+            },
+        );
+        assert_eq!(
+            unsupported_res.unwrap_err(),
+            ResponseCode::UnsupportedAggregationMethod
+        );
+
+        // Test inverted case
+        let data = vec![2.0];
+        let params = AggregationParams {
+            minimum_source_count: 1,
+            ..Default::default()
+        };
+        let res = aggregate_value(&data, &[1], &params);
+        assert_eq!(res.unwrap().rate, 2000000000);
+        let inverted_res = aggregate_value(
+            &data,
+            &[1],
+            &AggregationParams {
+                invert: true,
+                ..params
+            },
+        );
+        assert_eq!(inverted_res.unwrap().rate, 500000000);
+
+        // Test inverted negative case
+        let data = vec![-2.0];
+        let inverted_res = aggregate_value(
+            &data,
+            &[1],
+            &AggregationParams {
+                invert: true,
+                ..params
+            },
+        );
+        assert_eq!(inverted_res.unwrap_err(), ResponseCode::NegativeRate);
+        let overflow_res = aggregate_value(&[1e11], &[1], &params);
+        assert_eq!(overflow_res.unwrap_err(), ResponseCode::Overflow);
+
+        // Test inverted zero case
+        let data = vec![0.0];
+        let inverted_res = aggregate_value(
+            &data,
+            &[1],
+            &AggregationParams {
+                invert: true,
+                ..params
+            },
+        );
+        assert_eq!(inverted_res.unwrap_err(), ResponseCode::ConversionError);
+
+        // Test not enough sources case
+        let invalid_data = vec![];
+        let overflow_res = aggregate_value(&invalid_data, &[], &params);
+        assert_eq!(overflow_res.unwrap_err(), ResponseCode::NotEnoughSources);
+
+        // Test degraded case
+        let data = vec![1.5];
+        let degraded_params = AggregationParams {
+            minimum_source_count: 3,
+            allow_degraded: true,
+            ..params
+        };
+        let degraded_res = aggregate_value(&data, &[1], &degraded_params).unwrap();
+        assert_eq!(degraded_res.rate, 1500000000);
+        assert!(degraded_res.degraded);
+        let degraded_res = aggregate_value(&[1.0, 2.0, 3.0], &[1; 3], &degraded_params).unwrap();
+        assert!(!degraded_res.degraded);
+        let degraded_res = aggregate_value(&[], &[], &degraded_params);
+        assert_eq!(degraded_res.unwrap_err(), ResponseCode::NotEnoughSources);
+        let not_degraded_res = aggregate_value(
+            &data,
+            &[1],
+            &AggregationParams {
+                allow_degraded: false,
+                ..degraded_params
+            },
+        );
+        assert_eq!(
+            not_degraded_res.unwrap_err(),
+            ResponseCode::NotEnoughSources
+        );
+
+        // Test price jump case
+        let data = vec![1.0, 1.0, 1.0];
+        let jump_params = AggregationParams {
+            previous_rate: 950000000,
+            max_jump_percent: 10,
+            ..params
+        };
+        let within_res = aggregate_value(&data, &[1, 1, 1], &jump_params);
+        assert_eq!(within_res.unwrap().rate, 1000000000);
+        let data = vec![9.5, 9.5, 9.5];
+        let jump_res = aggregate_value(&data, &[1, 1, 1], &jump_params);
+        assert_eq!(jump_res.unwrap_err(), ResponseCode::PriceJump);
+        let unchecked_res = aggregate_value(
+            &data,
+            &[1, 1, 1],
+            &AggregationParams {
+                previous_rate: 0,
+                ..jump_params
+            },
+        );
+        assert_eq!(unchecked_res.unwrap().rate, 9500000000);
+    }
+
+    #[test]
+    fn test_aggregate_value_secondary_rate() {
+        let params = AggregationParams {
+            minimum_source_count: 4,
+            ..Default::default()
+        };
+
+        // Test median between observed rates case
+        let data = vec![1.0, 2.0, 3.0, 4.0];
+        let value = aggregate_value(&data, &[1; 4], &params).unwrap();
+        assert_eq!(value.rate, 2500000000);
+        assert_eq!(value.secondary_rate, 2500000000);
+
+        // Test mean differing from median case
+        let data = vec![1.0, 2.0, 3.0, 10.0];
+        let value = aggregate_value(&data, &[1; 4], &params).unwrap();
+        assert_eq!(value.rate, 2500000000);
+        assert_eq!(value.secondary_rate, 4000000000);
+
+        // Test secondary rate converted like the rate case
+        let value = aggregate_value(
+            &data,
+            &[1; 4],
+            &AggregationParams {
+                quote_rate: Some(2.0),
+                ..params
+            },
+        )
+        .unwrap();
+        assert_eq!(value.rate, 1250000000);
+        assert_eq!(value.secondary_rate, 2000000000);
+    }
+
+    #[test]
+    fn test_aggregate_high_precision_value() {
+        let params = AggregationParams {
+            minimum_source_count: 1,
+            multiplier: 1000000000000000000,
+            ..Default::default()
+        };
+
+        // Test rate overflowing a u64 case
+        let data = vec![100.0];
+        let overflow_res = aggregate_value(&data, &[1], &params);
+        assert_eq!(overflow_res.unwrap_err(), ResponseCode::Overflow);
+        let high_precision_res = aggregate_high_precision_value(&data, &[1], &params);
+        assert_eq!(high_precision_res.unwrap(), 100000000000000000000);
+
+        // Test rate fitting a u64 case
+        let data = vec![1.5];
+        let value = aggregate_value(&data, &[1], &params).unwrap();
+        let high_precision_res = aggregate_high_precision_value(&data, &[1], &params);
+        assert_eq!(high_precision_res.unwrap(), value.rate as u128);
+
+        // Test rate overflowing a u128 case
+        let data = vec![1e12];
+        let high_precision_res = aggregate_high_precision_value(
+            &data,
+            &[1],
+            &AggregationParams {
+                quote_rate: Some(1e-30),
+                ..params
+            },
+        );
+        assert_eq!(high_precision_res.unwrap_err(), ResponseCode::Overflow);
+
+        // Test error case
+        let high_precision_res = aggregate_high_precision_value(&[], &[], &params);
+        assert_eq!(
+            high_precision_res.unwrap_err(),
+            ResponseCode::NotEnoughSources
+        );
+    }
+
+    #[test]
+    fn test_get_high_precision_responses() {
+        let symbols = vec!["PSWAP".to_string(), "XOR".to_string(), "DNE".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([
+                (
+                    String::from("PSWAP"),
+                    vec![(DataSources::DS1INCHETH, 100.0)],
+                ),
+                (String::from("XOR"), vec![(DataSources::DS1INCHETH, 2.5)]),
+            ]),
+            ..Default::default()
+        };
+        let input = Input {
+            minimum_source_count: 1,
+            high_precision: true,
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(responses[0].response_code, ResponseCode::Overflow as u8);
+        let high_precision_responses =
+            get_high_precision_responses(&symbols, &source_data, &input, &responses);
+        assert_eq!(
+            high_precision_responses,
+            vec![
+                HighPrecisionResponse::new(
+                    "PSWAP".to_string(),
+                    ResponseCode::Success,
+                    100000000000000000000
+                ),
+                HighPrecisionResponse::new("XOR".to_string(), ResponseCode::Success, 2500000000),
+                HighPrecisionResponse::new("DNE".to_string(), ResponseCode::SymbolNotSupported, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_deviation_bps() {
+        // Test higher rate case
+        assert_eq!(get_deviation_bps(1010000000, 1000000000), 100);
+        assert_eq!(get_deviation_bps(1000050000, 1000000000), 0);
+
+        // Test lower rate case
+        assert_eq!(get_deviation_bps(990000000, 1000000000), -100);
+        assert_eq!(get_deviation_bps(0, 1000000000), -10000);
+
+        // Test equal rate case
+        assert_eq!(get_deviation_bps(1000000000, 1000000000), 0);
+
+        // Test saturated case
+        assert_eq!(get_deviation_bps(u64::MAX, 1), i32::MAX);
+    }
+
+    #[test]
+    fn test_is_price_jump() {
+        // Test within tolerance case
+        assert!(!is_price_jump(105, 100, 5));
+        assert!(!is_price_jump(95, 100, 5));
+
+        // Test beyond tolerance case
+        assert!(is_price_jump(106, 100, 5));
+        assert!(is_price_jump(1000, 100, 5));
+
+        // Test disabled case
+        assert!(!is_price_jump(1000, 0, 5));
+        assert!(!is_price_jump(1000, 100, 0));
+
+        // Test large rate case
+        assert!(!is_price_jump(u64::MAX as u128, u64::MAX - 1, 1));
+        assert!(is_price_jump(u128::MAX, 1, u16::MAX));
+    }
+
+    #[test]
+    fn test_get_aggregation_method() {
+        // Test declared method case
+        let input = Input {
+            aggregation_method: AggregationMethodCode(AggregationMethod::Median as u8),
+            ..Default::default()
+        };
+        let method = get_aggregation_method("RLB", &input);
+        assert_eq!(method, AggregationMethod::TrimmedMean as u8);
+        let params = get_aggregation_params(0, "RLB", &input, None, None);
+        assert_eq!(
+            params.aggregation_method,
+            AggregationMethod::TrimmedMean as u8
+        );
+
+        // Test caller override case
+        let input = Input {
+            override_method: true,
+            ..input
+        };
+        let method = get_aggregation_method("RLB", &input);
+        assert_eq!(method, AggregationMethod::Median as u8);
+
+        // Test no declared method case
+        let input = Input {
+            aggregation_method: AggregationMethodCode(AggregationMethod::Mean as u8),
+            ..Default::default()
+        };
+        let method = get_aggregation_method("XOR", &input);
+        assert_eq!(method, AggregationMethod::Mean as u8);
+    }
+
+    #[test]
+    fn test_get_missing_sources() {
+        let source_data = SourceData {
+            prices: HashMap::from([(String::from("WBTC"), vec![(DataSources::DS1INCHETH, 2.0)])]),
+            ..Default::default()
+        };
+
+        // Test partially responded case
+        let missing_sources = get_missing_sources(0, "WBTC", &source_data, &Input::default());
+        assert_eq!(missing_sources, vec![716, 719]);
+
+        // Test alias case
+        let missing_sources = get_missing_sources(0, "BTC", &source_data, &Input::default());
+        assert_eq!(missing_sources, vec![716, 719]);
+
+        // Test source mask case
+        let input = Input {
+            source_masks: vec![0b011],
+            ..Default::default()
+        };
+        let missing_sources = get_missing_sources(0, "WBTC", &source_data, &input);
+        assert_eq!(missing_sources, vec![716]);
+
+        // Test no prices case
+        let missing_sources = get_missing_sources(0, "XOR", &source_data, &Input::default());
+        assert_eq!(missing_sources, vec![715, 716]);
+
+        // Test unsupported symbol case
+        let missing_sources = get_missing_sources(0, "DNE", &source_data, &Input::default());
+        assert!(missing_sources.is_empty());
+    }
+
+    #[test]
+    fn test_get_required_source_count() {
+        // Test caller minimum case
+        assert_eq!(get_required_source_count(3, 2), 3);
+
+        // Test declared minimum case
+        assert_eq!(get_required_source_count(1, 2), 2.max(MIN_ABSOLUTE_SOURCES));
+
+        // Test absolute floor case
+        assert_eq!(get_required_source_count(0, 0), MIN_ABSOLUTE_SOURCES);
+
+        // Test low percentage case, which still cannot go below the absolute floor
+        let count = get_minimum_source_count(0, &[], 101, 0);
+        assert_eq!(count, 0);
+        assert_eq!(get_required_source_count(count, 0), MIN_ABSOLUTE_SOURCES);
+        let rates = vec![1.0; MIN_ABSOLUTE_SOURCES - 1];
+        let params = AggregationParams {
+            minimum_source_count: count,
+            ..Default::default()
+        };
+        let floor_res = aggregate_value(&rates, &vec![1; rates.len()], &params);
+        assert_eq!(floor_res.unwrap_err(), ResponseCode::NotEnoughSources);
+        let rates = vec![1.0; MIN_ABSOLUTE_SOURCES];
+        let floor_res = aggregate_value(&rates, &vec![1; rates.len()], &params);
+        assert_eq!(floor_res.unwrap().rate, 1000000000);
+    }
+
+    #[test]
+    fn test_get_minimum_source_count() {
+        // Test override case
+        assert_eq!(get_minimum_source_count(0, &[1, 3], 2, 2), 1);
+        assert_eq!(get_minimum_source_count(1, &[1, 3], 2, 2), 3);
+
+        // Test shorter than symbols case
+        assert_eq!(get_minimum_source_count(2, &[1, 3], 2, 2), 2);
+
+        // Test empty case
+        assert_eq!(get_minimum_source_count(0, &[], 2, 2), 2);
+
+        // Test percentage case
+        assert_eq!(get_minimum_source_count(0, &[], 150, 1), 1);
+        assert_eq!(get_minimum_source_count(0, &[], 150, 3), 2);
+        assert_eq!(get_minimum_source_count(0, &[200], 2, 2), 2);
+        assert_eq!(get_minimum_source_count(0, &[], 101, 3), 1);
+
+        // Test percentage above 100 case
+        assert_eq!(get_minimum_source_count(0, &[], 255, 3), 3);
+    }
+
+    #[test]
+    fn test_get_failure_reason() {
+        // Test success case
+        let reason = get_failure_reason(ResponseCode::Success, 2, 2);
+        assert_eq!(reason, "");
+
+        // Test not enough sources case
+        let reason = get_failure_reason(ResponseCode::NotEnoughSources, 1, 2);
+        assert_eq!(reason, "only 1 of 2 sources responded");
+
+        // Test other failure case
+        let reason = get_failure_reason(ResponseCode::StaleData, 2, 2);
+        assert_eq!(reason, "source outputs are stale");
+
+        // Test empty data case
+        let reason = get_failure_reason(ResponseCode::EmptyData, 0, 2);
+        assert_eq!(reason, "no source rates were left to aggregate");
+
+        // Test unknown case
+        let reason = get_failure_reason(ResponseCode::Unknown, 2, 2);
+        assert_eq!(reason, "aggregation found no rate for finite source rates");
+    }
+
+    #[test]
+    fn test_cross_check_groups() {
+        let symbols = vec!["stETH".to_string(), "wstETH".to_string(), "ETH".to_string()];
+        let get_source_data = |wsteth_rate: f64| SourceData {
+            prices: HashMap::from([
+                (String::from("stETH"), vec![(DataSources::DS1INCHETH, 1.2)]),
+                (
+                    String::from("wstETH"),
+                    vec![(DataSources::DS1INCHETH, wsteth_rate)],
+                ),
+                (String::from("WETH"), vec![(DataSources::DS1INCHETH, 1.19)]),
+            ]),
+            ..Default::default()
+        };
+        let input = Input {
+            minimum_source_count: 1,
+            cross_check_group: true,
+            ..Default::default()
+        };
+
+        // Test plausible group case
+        let responses = get_responses(&symbols, &get_source_data(1.3), &input);
+        assert!(responses
+            .iter()
+            .all(|r| r.response_code == ResponseCode::Success as u8));
+
+        // Test plausible group with inverted and quoted members case
+        let transformed_input = Input {
+            minimum_source_count: 1,
+            cross_check_group: true,
+            invert: vec![false, true],
+            quote_symbol: "WETH".to_string(),
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &get_source_data(1.3), &transformed_input);
+        assert!(responses
+            .iter()
+            .all(|r| r.response_code == ResponseCode::Success as u8));
+
+        // Test implausible group with inverted and quoted members case
+        let responses = get_responses(&symbols, &get_source_data(1.1), &transformed_input);
+        assert_eq!(
+            responses
+                .iter()
+                .map(|r| ResponseCode::from(r.response_code))
+                .collect::<Vec<ResponseCode>>(),
+            vec![
+                ResponseCode::SourceDisagreement,
+                ResponseCode::SourceDisagreement,
+                ResponseCode::Success
+            ]
+        );
+
+        // Test implausible stETH above wstETH case
+        let responses = get_responses(&symbols, &get_source_data(1.1), &input);
+        let codes = responses
+            .iter()
+            .map(|r| ResponseCode::from(r.response_code))
+            .collect::<Vec<ResponseCode>>();
+        assert_eq!(
+            codes,
+            vec![
+                ResponseCode::SourceDisagreement,
+                ResponseCode::SourceDisagreement,
+                ResponseCode::Success
+            ]
+        );
+        assert_eq!(responses[0].rate, 0);
+        assert_eq!(responses[2].rate, 1190000000);
+
+        // Test disabled cross check case
+        let input = Input {
+            cross_check_group: false,
+            ..input
+        };
+        let responses = get_responses(&symbols, &get_source_data(1.1), &input);
+        assert!(responses
+            .iter()
+            .all(|r| r.response_code == ResponseCode::Success as u8));
+
+        // Test partially requested group case
+        let input = Input {
+            cross_check_group: true,
+            ..input
+        };
+        let responses = get_responses(&symbols[..1], &get_source_data(1.1), &input);
+        assert_eq!(responses[0].response_code, ResponseCode::Success as u8);
+    }
+
+    #[test]
+    fn test_get_responses() {
+        let symbols = vec!["BTC".to_string(), "ETH".to_string(), "DNE".to_string()];
+        let warning_source_data = SourceData {
+            prices: HashMap::from([(
+                String::from("WETH"),
+                vec![
+                    (DataSources::DS1INCHETH, 2.31),
+                    (DataSources::DSARKENETH, 2.32),
+                ],
+            )]),
+            ..Default::default()
+        };
+
+        // Test success with warning case
+        let input = Input {
+            minimum_source_count: 2,
+            report_warnings: true,
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols[1..2], &warning_source_data, &input);
+        assert_eq!(
+            responses[0].response_code,
+            ResponseCode::SuccessWithWarning as u8
+        );
+        assert_eq!(responses[0].rate, 2315000000);
+        let input = Input {
+            report_warnings: false,
+            ..input
+        };
+        let responses = get_responses(&symbols[1..2], &warning_source_data, &input);
+        assert_eq!(responses[0].response_code, ResponseCode::Success as u8);
+        assert_eq!(responses[0].rate_str, "");
+
+        // Test multiple methods case
+        let input = Input {
+            methods: vec![
+                AggregationMethodCode(AggregationMethod::Median as u8),
+                AggregationMethodCode(AggregationMethod::Mean as u8),
+            ],
+            ..input
+        };
+        let responses = get_responses(&symbols[1..2], &warning_source_data, &input);
+        assert_eq!(responses[0].method_rates, vec![2315000000, 2315000000]);
+        let input = Input {
+            methods: vec![],
+            ..input
+        };
+        let responses = get_responses(&symbols[1..2], &warning_source_data, &input);
+        assert!(responses[0].method_rates.is_empty());
+
+        // Test deviation case
+        let input = Input {
+            reference_rates: vec![2292000000, 2340000000],
+            report_deviation: true,
+            ..input
+        };
+        let responses = get_responses(&symbols[1..2], &warning_source_data, &input);
+        assert_eq!(responses[0].deviation_bps, 100);
+        let input = Input {
+            reference_rates: vec![2340000000],
+            ..input
+        };
+        let responses = get_responses(&symbols[1..2], &warning_source_data, &input);
+        assert_eq!(responses[0].deviation_bps, -106);
+        assert_eq!(responses[0].rate, 2315000000);
+
+        // Test deviation disabled case
+        let input = Input {
+            reference_rates: vec![0],
+            ..input
+        };
+        let responses = get_responses(&symbols[1..2], &warning_source_data, &input);
+        assert_eq!(responses[0].deviation_bps, 0);
+        let input = Input {
+            reference_rates: vec![2340000000],
+            report_deviation: false,
+            ..input
+        };
+        let responses = get_responses(&symbols[1..2], &warning_source_data, &input);
+        assert_eq!(responses[0].deviation_bps, 0);
+
+        // Test string rates case
+        let input = Input {
+            string_rates: true,
+            ..input
+        };
+        let responses = get_responses(&symbols[1..], &warning_source_data, &input);
+        assert_eq!(responses[0].rate_str, "2.315000000");
+        assert_eq!(responses[1].rate_str, "");
+
+        let source_data = SourceData {
+            prices: HashMap::from([
+                (
+                    String::from("WBTC"),
+                    vec![
+                        (DataSources::DS1INCHETH, 1.23),
+                        (DataSources::DSARKENETH, 1.24),
+                        (DataSources::DS1INCHBSC, 1.25),
+                        (DataSources::DSARKENBSC, 1.26),
+                        (DataSources::DS1INCHETH, 1.27),
+                    ],
+                ),
+                (
+                    String::from("WETH"),
+                    vec![
+                        (DataSources::DS1INCHETH, 2.31),
+                        (DataSources::DSARKENETH, 2.32),
+                    ],
+                ),
+            ]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 3,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("BTC".to_string(), ResponseCode::Success, 1250000, 5)
+                .with_spread(32000000)
+        );
+        assert_eq!(
+            responses[1],
+            Response::new("ETH".to_string(), ResponseCode::NotEnoughSources, 0, 2)
+                .with_chain_id(Chain::Eth)
+        );
+        assert_eq!(
+            responses[2],
+            Response::new("DNE".to_string(), ResponseCode::SymbolNotSupported, 0, 0)
+        );
+
+        // Test malformed symbol case
+        let responses = get_responses(
+            &["WB TC".to_string()],
+            &source_data,
+            &Input {
+                minimum_source_count: 3,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("WB TC".to_string(), ResponseCode::SymbolNotSupported, 0, 0)
+        );
+
+        // Test canonical symbol case
+        let symbols = vec!["weth".to_string(), "StETH".to_string(), "foo".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([
+                (String::from("WETH"), vec![(DataSources::DS1INCHETH, 2.31)]),
+                (String::from("stETH"), vec![(DataSources::DS1INCHETH, 2.30)]),
+            ]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("WETH".to_string(), ResponseCode::Success, 2310000000, 1)
+                .with_chain_id(Chain::Eth)
+        );
+        assert_eq!(
+            responses[1],
+            Response::new("stETH".to_string(), ResponseCode::Success, 2300000000, 1)
+                .with_chain_id(Chain::Eth)
+        );
+        assert_eq!(
+            responses[2],
+            Response::new("foo".to_string(), ResponseCode::SymbolNotSupported, 0, 0)
+        );
+
+        // Test stale case
+        let symbols = vec!["WETH".to_string(), "XOR".to_string()];
+        let mut source_data = SourceData {
+            prices: HashMap::from([
+                (String::from("WETH"), vec![(DataSources::DS1INCHETH, 2.31)]),
+                (String::from("XOR"), vec![(DataSources::DS1INCHETH, 2.30)]),
+            ]),
+            ..Default::default()
+        };
+        source_data.stale_symbols = HashSet::from([String::from("WETH")]);
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("WETH".to_string(), ResponseCode::StaleData, 0, 1)
+                .with_chain_id(Chain::Eth)
+        );
+        assert_eq!(
+            responses[1],
+            Response::new("XOR".to_string(), ResponseCode::Success, 2300000000, 1)
+                .with_chain_id(Chain::Eth)
+        );
+
+        // Test low participation case
+        let symbols = vec!["WETH".to_string(), "XOR".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([
+                (String::from("WETH"), vec![(DataSources::DS1INCHETH, 2.31)]),
+                (String::from("XOR"), vec![(DataSources::DS1INCHETH, 2.30)]),
+            ]),
+            low_participation_symbols: HashSet::from([String::from("WETH")]),
+            ..Default::default()
+        };
+        let input = Input {
+            minimum_source_count: 1,
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses[0],
+            Response::new("WETH".to_string(), ResponseCode::LowParticipation, 0, 1)
+                .with_chain_id(Chain::Eth)
+        );
+        assert_eq!(responses[1].response_code, ResponseCode::Success as u8);
+
+        // Test duplicated symbol case
+        let symbols = vec!["XOR".to_string(), "XOR".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([(String::from("XOR"), vec![(DataSources::DS1INCHETH, 2.30)])]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(responses.len(), 2);
+        assert_eq!(
+            responses[0],
+            Response::new("XOR".to_string(), ResponseCode::Success, 2300000000, 1)
+                .with_chain_id(Chain::Eth)
+        );
+        assert_eq!(responses[0], responses[1]);
+
+        // Test parse error case
+        let symbols = vec!["WETH".to_string(), "XOR".to_string()];
+        let source_data = SourceData {
+            received_symbols: HashSet::from([String::from("WETH"), String::from("XOR")]),
+            parsed_symbols: HashSet::from([String::from("XOR")]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("WETH".to_string(), ResponseCode::ParseError, 0, 0)
+        );
+        assert_eq!(
+            responses[1],
+            Response::new("XOR".to_string(), ResponseCode::SymbolNotSupported, 0, 0)
+        );
+
+        // Test empty source case
+        let symbols = vec!["WETH".to_string(), "XOR".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([(String::from("XOR"), vec![(DataSources::DSARKENETH, 2.3)])]),
+            empty_symbols: HashSet::from([String::from("WETH"), String::from("XOR")]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("WETH".to_string(), ResponseCode::SourceEmpty, 0, 0)
+        );
+        assert_eq!(responses[1].response_code, ResponseCode::Success as u8);
+
+        // Test inverted case
+        let symbols = vec!["XOR".to_string(), "XOR".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([(String::from("XOR"), vec![(DataSources::DS1INCHETH, 2.0)])]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                invert: vec![false, true],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("XOR".to_string(), ResponseCode::Success, 2000000000, 1)
+                .with_chain_id(Chain::Eth)
+        );
+        assert_eq!(
+            responses[1],
+            Response::new("XOR".to_string(), ResponseCode::Success, 500000000, 1)
+                .with_chain_id(Chain::Eth)
+        );
+
+        // Test alias case
+        let symbols = vec!["btc".to_string(), "DOGE".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([(
+                String::from("WBTC"),
+                vec![
+                    (DataSources::DS1INCHETH, 2.0),
+                    (DataSources::DSARKENETH, 2.0),
+                ],
+            )]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("BTC".to_string(), ResponseCode::Success, 2000000, 2)
+                .with_chain_id(Chain::Eth)
+        );
+        assert_eq!(responses[0].sources_requested, 3);
+        assert_eq!(
+            responses[1],
+            Response::new("DOGE".to_string(), ResponseCode::SymbolNotSupported, 0, 0)
+        );
+
+        // Test quote symbol case
+        let symbols = vec!["WBTC".to_string(), "foo".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([
+                (
+                    String::from("WBTC"),
+                    vec![
+                        (DataSources::DS1INCHETH, 30000.0),
+                        (DataSources::DSARKENETH, 30000.0),
+                    ],
+                ),
+                (
+                    String::from("WETH"),
+                    vec![(DataSources::DS1INCHETH, 2000.0)],
+                ),
+            ]),
+            ..Default::default()
+        };
+        let input = Input {
+            minimum_source_count: 1,
+            quote_symbol: "WETH".to_string(),
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses[0],
+            Response::new("WBTC".to_string(), ResponseCode::Success, 15000000, 2)
+                .with_chain_id(Chain::Eth)
+        );
+        assert_eq!(
+            responses[1],
+            Response::new("foo".to_string(), ResponseCode::SymbolNotSupported, 0, 0)
+        );
+
+        // Test missing quote price case
+        let input = Input {
+            minimum_source_count: 1,
+            quote_symbol: "XOR".to_string(),
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses[0],
+            Response::new("WBTC".to_string(), ResponseCode::ConversionError, 0, 2)
+                .with_chain_id(Chain::Eth)
+        );
+
+        // Test quote symbol with percentage minimum case
+        let mut percentage_source_data = SourceData {
+            prices: source_data.prices.clone(),
+            ..Default::default()
+        };
+        let input = Input {
+            minimum_source_count: (PERCENTAGE_OFFSET + 50) as u8,
+            quote_symbol: "WETH".to_string(),
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &percentage_source_data, &input);
+        assert_eq!(responses[0].code(), ResponseCode::ConversionError);
+        percentage_source_data.prices.insert(
+            String::from("WETH"),
+            vec![
+                (DataSources::DS1INCHETH, 2000.0),
+                (DataSources::DSARKENETH, 2000.0),
+            ],
+        );
+        let responses = get_responses(&symbols, &percentage_source_data, &input);
+        assert_eq!(
+            responses[0],
+            Response::new("WBTC".to_string(), ResponseCode::Success, 15000000, 2)
+                .with_chain_id(Chain::Eth)
+        );
+
+        // Test quote symbol with declared minimum case
+        let input = Input {
+            minimum_source_count: 1,
+            quote_symbol: "WBTC".to_string(),
+            ..Default::default()
+        };
+        let responses = get_responses(&["WETH".to_string()], &percentage_source_data, &input);
+        assert_eq!(responses[0].code(), ResponseCode::Success);
+        percentage_source_data.prices.insert(
+            String::from("WBTC"),
+            vec![(DataSources::DS1INCHETH, 30000.0)],
+        );
+        let responses = get_responses(&["WETH".to_string()], &percentage_source_data, &input);
+        assert_eq!(responses[0].code(), ResponseCode::ConversionError);
+
+        // Test multiple quote symbols case
+        let mut source_data = source_data;
+        source_data.prices.insert(
+            String::from("stETH"),
+            vec![(DataSources::DS1INCHETH, 1500.0)],
+        );
+        let input = Input {
+            minimum_source_count: 1,
+            quote_symbols: vec!["WETH".to_string(), "stETH".to_string(), "XOR".to_string()],
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses
+                .iter()
+                .map(|r| (r.symbol.as_str(), r.code(), r.rate, r.decimals))
+                .collect::<Vec<(&str, ResponseCode, u64, u8)>>(),
+            vec![
+                ("WBTC/WETH", ResponseCode::Success, 15000000, 6),
+                ("WBTC/stETH", ResponseCode::Success, 20000000, 6),
+                ("WBTC/XOR", ResponseCode::ConversionError, 0, 6),
+                ("foo/WETH", ResponseCode::SymbolNotSupported, 0, 9),
+                ("foo/stETH", ResponseCode::SymbolNotSupported, 0, 9),
+                ("foo/XOR", ResponseCode::SymbolNotSupported, 0, 9),
+            ]
+        );
+
+        // Test multiple quote symbols high precision case
+        let high_precision_responses =
+            get_high_precision_responses(&symbols, &source_data, &input, &responses);
+        assert_eq!(high_precision_responses.len(), 6);
+        assert_eq!(
+            high_precision_responses[1],
+            HighPrecisionResponse::new("WBTC/stETH".to_string(), ResponseCode::Success, 20000000)
+        );
+        assert_eq!(
+            high_precision_responses[2],
+            HighPrecisionResponse::new("WBTC/XOR".to_string(), ResponseCode::ConversionError, 0)
+        );
+
+        // Test skip unsupported case
+        let symbols = vec!["foo".to_string(), "XOR".to_string(), "DNE".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([(String::from("XOR"), vec![(DataSources::DS1INCHETH, 2.0)])]),
+            ..Default::default()
+        };
+        let input = Input {
+            minimum_source_count: 1,
+            invert: vec![false, true],
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(responses.len(), 3);
+        assert_eq!(
+            responses[0],
+            Response::new("foo".to_string(), ResponseCode::SymbolNotSupported, 0, 0)
+        );
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                skip_unsupported: true,
+                ..input
+            },
+        );
+        assert_eq!(
+            responses,
+            vec![
+                Response::new("XOR".to_string(), ResponseCode::Success, 500000000, 1)
+                    .with_chain_id(Chain::Eth)
+            ]
+        );
+
+        // Test verbose errors case
+        let symbols = vec!["XOR".to_string(), "foo".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([(String::from("XOR"), vec![(DataSources::DS1INCHETH, 2.0)])]),
+            ..Default::default()
+        };
+        let input = Input {
+            minimum_source_count: 2,
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(responses[0].reason, "");
+        let input = Input {
+            verbose_errors: true,
+            ..input
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses[0],
+            Response::new("XOR".to_string(), ResponseCode::NotEnoughSources, 0, 1)
+                .with_chain_id(Chain::Eth)
+                .with_reason("only 1 of 2 sources responded".to_string())
+                .with_missing_sources(vec![716])
+        );
+        assert_eq!(responses[1].reason, "symbol is not supported");
+
+        // Test discarded rows case
+        let source_data = SourceData {
+            discarded_rows: HashMap::from([(String::from("XOR"), 2)]),
+            ..source_data
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses[0].reason,
+            "only 1 of 2 sources responded, 2 malformed rows discarded"
+        );
+        let input = Input {
+            minimum_source_count: 1,
+            ..input
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(responses[0].response_code, ResponseCode::Success as u8);
+        assert_eq!(responses[0].reason, "2 malformed rows discarded");
+
+        // Test missing sources case
+        let symbols = vec!["WBTC".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([(String::from("WBTC"), vec![(DataSources::DS1INCHETH, 2.0)])]),
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &Input::default());
+        assert!(responses[0].missing_sources.is_empty());
+        let input = Input {
+            verbose_errors: true,
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses[0].response_code,
+            ResponseCode::NotEnoughSources as u8
+        );
+        assert_eq!(responses[0].missing_sources, vec![716, 719]);
+
+        // Test sources requested case
+        let symbols = vec!["VC".to_string(), "WBTC".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([(String::from("WBTC"), vec![(DataSources::DS1INCHETH, 2.0)])]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 2,
+                ..Default::default()
+            },
+        );
+        assert_eq!(responses[0].sources_requested, 1);
+        assert_eq!(
+            responses[0].response_code,
+            ResponseCode::SymbolNotSupported as u8
+        );
+        assert_eq!(responses[1].sources_requested, 3);
+        assert_eq!(
+            responses[1].response_code,
+            ResponseCode::NotEnoughSources as u8
+        );
+
+        // Test chain tagging case
+        let symbols = vec!["WBTC".to_string(), "VC".to_string(), "XOR".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([
+                (
+                    String::from("WBTC"),
+                    vec![
+                        (DataSources::DS1INCHETH, 2.0),
+                        (DataSources::DSARKENETH, 2.0),
+                    ],
+                ),
+                (String::from("VC"), vec![(DataSources::DS1INCHBSC, 2.0)]),
+                (
+                    String::from("XOR"),
+                    vec![
+                        (DataSources::DS1INCHETH, 2.0),
+                        (DataSources::DS1INCHBSC, 2.0),
+                    ],
+                ),
+            ]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(responses[0].chain_id, Chain::Eth as u8);
+        assert_eq!(responses[1].chain_id, Chain::Bsc as u8);
+        assert_eq!(responses[2].chain_id, Chain::Mixed as u8);
+
+        // Test declared minimum source count case
+        let symbols = vec!["WBTC".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([(String::from("WBTC"), vec![(DataSources::DS1INCHETH, 2.0)])]),
+            ..Default::default()
+        };
+        let input = Input {
+            minimum_source_count: 1,
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses[0],
+            Response::new("WBTC".to_string(), ResponseCode::NotEnoughSources, 0, 1)
+                .with_chain_id(Chain::Eth)
+        );
+
+        // Test per-symbol minimum source count case
+        let symbols = vec!["VC".to_string(), "PHB".to_string()];
+        let source_data = SourceData {
+            prices: HashMap::from([
+                (String::from("VC"), vec![(DataSources::DS1INCHBSC, 0.05)]),
+                (String::from("PHB"), vec![(DataSources::DS1INCHBSC, 0.4)]),
+            ]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 2,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("VC".to_string(), ResponseCode::NotEnoughSources, 0, 1)
+                .with_chain_id(Chain::Bsc)
+        );
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 2,
+                minimum_source_counts: vec![1],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("VC".to_string(), ResponseCode::Success, 50000000, 1)
+                .with_chain_id(Chain::Bsc)
+        );
+        assert_eq!(
+            responses[1],
+            Response::new("PHB".to_string(), ResponseCode::NotEnoughSources, 0, 1)
+                .with_chain_id(Chain::Bsc)
+        );
+
+        // Test secondary rate case
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                include_secondary: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("VC".to_string(), ResponseCode::Success, 50000000, 1)
+                .with_chain_id(Chain::Bsc)
+                .with_secondary_rate(50000000)
+        );
+
+        // Test degraded case
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 2,
+                allow_degraded: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("VC".to_string(), ResponseCode::Degraded, 50000000, 1)
+                .with_chain_id(Chain::Bsc)
+        );
+
+        // Test stats case
+        let source_data_with_stats = SourceData {
+            prices: HashMap::from([(
+                String::from("XOR"),
+                vec![
+                    (DataSources::DS1INCHETH, 1.0),
+                    (DataSources::DSARKENETH, 3.0),
+                ],
+            )]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &["XOR".to_string()],
+            &source_data_with_stats,
+            &Input {
+                minimum_source_count: 2,
+                include_stats: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("XOR".to_string(), ResponseCode::Success, 2000000000, 2)
+                .with_spread(1000000000)
+                .with_chain_id(Chain::Eth)
+                .with_std_dev(1414213562)
+        );
+
+        // Test stats with symbol decimals case
+        let source_data_with_stats = SourceData {
+            prices: HashMap::from([(
+                String::from("WBTC"),
+                vec![
+                    (DataSources::DS1INCHETH, 29990.0),
+                    (DataSources::DSARKENETH, 30010.0),
+                ],
+            )]),
+            ..Default::default()
+        };
+        let responses = get_responses(
+            &["WBTC".to_string()],
+            &source_data_with_stats,
+            &Input {
+                minimum_source_count: 2,
+                include_stats: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(responses[0].rate, 30000000000);
+        assert_eq!(responses[0].std_dev, 14142135);
+
+        // Test percentage minimum source count case
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_counts: vec![150, 200],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("VC".to_string(), ResponseCode::Success, 50000000, 1)
+                .with_chain_id(Chain::Bsc)
+        );
+        assert_eq!(
+            responses[1],
+            Response::new("PHB".to_string(), ResponseCode::NotEnoughSources, 0, 1)
+                .with_chain_id(Chain::Bsc)
+        );
+    }
+
+    #[test]
+    fn test_get_debug_rates() {
+        let source_data = SourceData {
+            prices: HashMap::from([(
+                String::from("XOR"),
+                vec![
+                    (DataSources::DS1INCHETH, 2.25),
+                    (DataSources::DSARKENETH, 2.75),
+                ],
+            )]),
+            ..Default::default()
+        };
+
+        // Test two-source symbol case
+        let symbols = vec!["XOR".to_string()];
+        let debug_rates = get_debug_rates(&symbols, &source_data, &Input::default());
+        assert_eq!(debug_rates, vec![vec![2250000000, 2750000000]]);
+
+        // Test missing symbol case
+        let symbols = vec!["xor".to_string(), "DNE".to_string()];
+        let debug_rates = get_debug_rates(&symbols, &source_data, &Input::default());
+        assert_eq!(debug_rates, vec![vec![2250000000, 2750000000], vec![]]);
+
+        // Test skipped unsupported symbol case
+        let input = Input {
+            skip_unsupported: true,
+            ..Default::default()
+        };
+        let debug_rates = get_debug_rates(&symbols, &source_data, &input);
+        assert_eq!(debug_rates, vec![vec![2250000000, 2750000000]]);
+
+        // Test multiple quote symbols case
+        let input = Input {
+            quote_symbols: vec!["WETH".to_string(), "stETH".to_string()],
+            ..Default::default()
+        };
+        let debug_rates = get_debug_rates(&symbols, &source_data, &input);
+        assert_eq!(
+            debug_rates,
+            vec![
+                vec![2250000000, 2750000000],
+                vec![2250000000, 2750000000],
+                vec![],
+                vec![],
+            ]
+        );
+        assert_eq!(
+            debug_rates.len(),
+            get_responses(&symbols, &source_data, &input).len()
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Stable fingerprints of requests, so results can be cached under a key that is the same for
+//! every semantically identical request.
+
+use obi::OBIEncode;
+use siphasher::sip128::SipHasher24;
+
+use crate::{canonicalize_symbol, get_input_symbols, Input};
+
+/// Fixed keys of the two SipHash halves of a fingerprint, so it is the same across executions
+/// and builds
+const FINGERPRINT_KEYS: [(u64, u64); 2] = [(0, 0), (0, 1)];
+
+/// A requested symbol along with the options that apply to it by index
+#[derive(OBIEncode, PartialEq, Eq, PartialOrd, Ord)]
+struct NormalizedSymbol {
+    symbol: String,
+    minimum_source_count: u8,
+    invert: bool,
+    previous_rate: u64,
+    source_mask: u8,
+    reference_rate: u64,
+}
+
+/// The parts of a request that affect its result, with the symbols as a sorted set
+#[derive(OBIEncode)]
+struct NormalizedRequest {
+    symbols: Vec<NormalizedSymbol>,
+    quote_symbol: String,
+    quote_symbols: Vec<String>,
+    methods: Vec<u8>,
+    minimum_source_count: u8,
+    aggregation_method: u8,
+    outlier_threshold: u8,
+    debug: bool,
+    max_deviation_bps: u16,
+    skip_unsupported: bool,
+    rounding: u8,
+    verbose_errors: bool,
+    max_jump_percent: u16,
+    include_secondary: bool,
+    strict: bool,
+    high_precision: bool,
+    include_stats: bool,
+    allow_degraded: bool,
+    report_warnings: bool,
+    even_rule: u8,
+    override_method: bool,
+    max_age_seconds: u32,
+    string_rates: bool,
+    cross_check_group: bool,
+    report_deviation: bool,
+}
+
+/// Resolves a symbol to its canonical casing, or lowercases it if it is not supported
+fn normalize_symbol(symbol: &str) -> String {
+    canonicalize_symbol(symbol).map_or_else(|| symbol.to_lowercase(), String::from)
+}
+
+/// Returns a 32 byte fingerprint of the request, made of two keyed 128-bit SipHash halves, that
+/// ignores the order, casing and duplicates of its symbols and quote symbols. Each symbol keeps
+/// the options given for it by index, so reordering the symbols along with their options does
+/// not change the fingerprint. Any new `Input` field affecting the result must be added to
+/// `NormalizedRequest`
+pub fn request_fingerprint(input: &Input) -> [u8; 32] {
+    let mut symbols = get_input_symbols(input)
+        .iter()
+        .enumerate()
+        .map(|(i, symbol)| NormalizedSymbol {
+            symbol: normalize_symbol(symbol),
+            minimum_source_count: input
+                .minimum_source_counts
+                .get(i)
+                .copied()
+                .unwrap_or(input.minimum_source_count),
+            invert: input.invert.get(i).copied().unwrap_or(false),
+            previous_rate: input.previous_rates.get(i).copied().unwrap_or(0),
+            source_mask: input.source_masks.get(i).copied().unwrap_or(0),
+            reference_rate: input.reference_rates.get(i).copied().unwrap_or(0),
+        })
+        .collect::<Vec<NormalizedSymbol>>();
+    symbols.sort();
+    symbols.dedup();
+    let mut quote_symbols = input
+        .quote_symbols
+        .iter()
+        .map(|symbol| normalize_symbol(symbol))
+        .collect::<Vec<String>>();
+    quote_symbols.sort();
+    quote_symbols.dedup();
+
+    let request = NormalizedRequest {
+        symbols,
+        quote_symbol: normalize_symbol(&input.quote_symbol),
+        quote_symbols,
+        methods: input.methods.iter().map(|method| method.0).collect(),
+        minimum_source_count: input.minimum_source_count,
+        aggregation_method: input.aggregation_method.0,
+        outlier_threshold: input.outlier_threshold,
+        debug: input.debug,
+        max_deviation_bps: input.max_deviation_bps,
+        skip_unsupported: input.skip_unsupported,
+        rounding: input.rounding.0,
+        verbose_errors: input.verbose_errors,
+        max_jump_percent: input.max_jump_percent,
+        include_secondary: input.include_secondary,
+        strict: input.strict,
+        high_precision: input.high_precision,
+        include_stats: input.include_stats,
+        allow_degraded: input.allow_degraded,
+        report_warnings: input.report_warnings,
+        even_rule: input.even_rule.0,
+        override_method: input.override_method,
+        max_age_seconds: input.max_age_seconds,
+        string_rates: input.string_rates,
+        cross_check_group: input.cross_check_group,
+        report_deviation: input.report_deviation,
+    };
+    let encoded = request.try_to_vec().unwrap_or_default();
+    let mut fingerprint = [0u8; 32];
+    for (half, (key0, key1)) in fingerprint.chunks_exact_mut(16).zip(FINGERPRINT_KEYS) {
+        half.copy_from_slice(
+            &SipHasher24::new_with_keys(key0, key1)
+                .hash(&encoded)
+                .as_bytes(),
+        );
+    }
+    fingerprint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_fingerprint() {
+        let input = Input {
+            symbols: vec!["WBTC".to_string(), "XOR".to_string(), "foo".to_string()],
+            minimum_source_count: 2,
+            invert: vec![true, false],
+            ..Default::default()
+        };
+        let fingerprint = request_fingerprint(&input);
+
+        // Test reordered equivalent case
+        let reordered = Input {
+            symbols: vec!["XOR".to_string(), "FOO".to_string(), "wbtc".to_string()],
+            minimum_source_count: 2,
+            invert: vec![false, false, true],
+            ..Default::default()
+        };
+        assert_eq!(request_fingerprint(&reordered), fingerprint);
+
+        // Test duplicated and aliased case
+        let duplicated = Input {
+            symbols: vec![
+                "BTC".to_string(),
+                "XOR".to_string(),
+                "foo".to_string(),
+                "xor".to_string(),
+            ],
+            minimum_source_count: 2,
+            invert: vec![true],
+            ..Default::default()
+        };
+        assert_eq!(request_fingerprint(&duplicated), fingerprint);
+
+        // Test explicit default option case
+        let explicit = Input {
+            symbols: vec!["WBTC".to_string(), "XOR".to_string(), "foo".to_string()],
+            minimum_source_count: 2,
+            minimum_source_counts: vec![2, 2, 2],
+            invert: vec![true, false, false],
+            previous_rates: vec![0, 0, 0, 7],
+            ..Default::default()
+        };
+        assert_eq!(request_fingerprint(&explicit), fingerprint);
+
+        // Test symbol index case
+        let table = crate::get_symbol_table();
+        let indexed = Input {
+            symbols: vec!["WBTC".to_string(), "foo".to_string()],
+            symbol_indices: vec![table.iter().position(|s| *s == "XOR").unwrap() as u16],
+            minimum_source_count: 2,
+            invert: vec![true],
+            ..Default::default()
+        };
+        assert_eq!(request_fingerprint(&indexed), fingerprint);
+
+        // Test reordered and recased quote symbols case
+        let quoted = Input {
+            symbols: vec!["WBTC".to_string()],
+            quote_symbols: vec!["WETH".to_string(), "stETH".to_string()],
+            ..Default::default()
+        };
+        let reordered = Input {
+            symbols: vec!["WBTC".to_string()],
+            quote_symbols: vec!["steth".to_string(), "weth".to_string(), "WETH".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            request_fingerprint(&quoted),
+            request_fingerprint(&reordered)
+        );
+        assert_ne!(request_fingerprint(&quoted), fingerprint);
+
+        // Test fingerprint halves case
+        assert_ne!(fingerprint[..16], fingerprint[16..]);
+
+        // Test differing threshold case
+        let differing = Input {
+            symbols: vec!["WBTC".to_string(), "XOR".to_string(), "foo".to_string()],
+            minimum_source_count: 3,
+            invert: vec![true, false],
+            ..Default::default()
+        };
+        assert_ne!(request_fingerprint(&differing), fingerprint);
+        let differing = Input {
+            outlier_threshold: 3,
+            minimum_source_count: 2,
+            ..differing
+        };
+        assert_ne!(request_fingerprint(&differing), fingerprint);
+
+        // Test differing per-symbol option case
+        let differing = Input {
+            symbols: vec!["WBTC".to_string(), "XOR".to_string(), "foo".to_string()],
+            minimum_source_count: 2,
+            invert: vec![false, true],
+            ..Default::default()
+        };
+        assert_ne!(request_fingerprint(&differing), fingerprint);
+    }
+}
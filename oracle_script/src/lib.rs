@@ -1,89 +1,790 @@
 use anyhow::{bail, Result};
-use num::{FromPrimitive, Integer};
+use num::Integer;
 use std::collections::HashMap;
 use std::iter::zip;
 
 use obi::{OBIDecode, OBIEncode, OBISchema};
+#[cfg(not(feature = "lib-only"))]
 use owasm_kit::{execute_entry_point, ext, oei, prepare_entry_point};
 use phf::phf_map;
 
+pub mod aggregation;
+pub mod fingerprint;
+
+use aggregation::{
+    filter_and_medianize, get_debug_rates, get_high_precision_responses, get_responses, median,
+    AggregationMethod, AggregationMethodCode, EvenRule, EvenRuleCode, MissingPolicy, RoundingMode,
+    RoundingModeCode, SourceData, PERCENTAGE_OFFSET,
+};
+
 const MULTIPLIER: u64 = 1000000000;
-const DATA_SOURCE_COUNT: usize = 4;
+const DEFAULT_DECIMALS: u32 = 9;
+const DATA_SOURCE_COUNT: usize = 5;
+const ALL_SYMBOLS: &str = "*";
+const MAX_REASONABLE_RATE: f64 = 1e12;
+const MAX_SYMBOLS: usize = 32;
+const MIN_PARTICIPATION_PERCENT: u64 = 50;
+const UNIT_SUFFIXES: &[&str] = &["USDT", "USD"];
+const MAX_OUTPUT_ROWS: usize = 8;
+const MAX_OUTPUT_BYTES: usize = 4096;
+const MAX_AGE_SECONDS: u32 = 86400;
+const MAX_CALLDATA_BYTES: usize = 256;
+const EXTERNAL_ID_STRIDE: i64 = 1000;
+const MAX_SYMBOL_LEN: usize = 16;
+const HEALTH_SYMBOL: &str = "__health__";
 
-#[derive(OBIDecode, OBISchema)]
-struct Input {
-    symbols: Vec<String>,
-    minimum_source_count: u8,
+/// Price of each symbol in a data source output, along with its volume if reported
+type Observation = Vec<(Option<f64>, Option<f64>)>;
+
+#[derive(OBIDecode, OBISchema, Default)]
+pub struct Input {
+    pub symbols: Vec<String>,
+    pub minimum_source_count: u8,
+    pub minimum_source_counts: Vec<u8>,
+    pub aggregation_method: AggregationMethodCode,
+    pub outlier_threshold: u8,
+    pub invert: Vec<bool>,
+    pub debug: bool,
+    pub max_deviation_bps: u16,
+    /// Symbol every rate is expressed in terms of, where an empty string keeps USD
+    pub quote_symbol: String,
+    /// Omits unsupported symbols from the output instead of reporting `SymbolNotSupported`
+    pub skip_unsupported: bool,
+    pub rounding: RoundingModeCode,
+    /// Explains each failed response in its reason field
+    pub verbose_errors: bool,
+    /// Previously reported scaled rate of each symbol, where 0 skips the jump check for it
+    pub previous_rates: Vec<u64>,
+    /// Maximum move allowed from a previous rate in percent, where 0 disables the jump check
+    pub max_jump_percent: u16,
+    /// Reports the mean of each symbol's rates alongside its aggregated rate
+    pub include_secondary: bool,
+    /// Rejects every response with `BatchRejected` if any symbol fails
+    pub strict: bool,
+    /// Also reports every rate as a u128 in the high precision responses
+    pub high_precision: bool,
+    /// Reports the standard deviation of each symbol's rates
+    pub include_stats: bool,
+    /// Reports the median of the available sources as `Degraded` instead of failing with
+    /// `NotEnoughSources`
+    pub allow_degraded: bool,
+    /// Reports `SuccessWithWarning` for rates aggregated from exactly the required number of
+    /// sources or from widely spread sources
+    pub report_warnings: bool,
+    /// Mask of each symbol's configured sources to use, where bit `i` selects its `i`th source
+    /// and 0 uses every source
+    pub source_masks: Vec<u8>,
+    /// Rule picking the median of an even number of validator reports, as an `EvenRule`
+    pub even_rule: EvenRuleCode,
+    /// Applies `aggregation_method` to every symbol, including those declaring their own method
+    pub override_method: bool,
+    /// Maximum age of the data the adapters may serve in seconds, where 0 leaves it to them
+    pub max_age_seconds: u32,
+    /// Also reports each successful rate as a decimal string in `rate_str`, e.g. `"1.234000000"`
+    /// for a rate of `1234000000` scaled by `MULTIPLIER`. The numeric rate is reported either way
+    pub string_rates: bool,
+    /// Fails the symbols of every equivalence group in `GROUPS` whose rates are implausibly far
+    /// apart with `SourceDisagreement`
+    pub cross_check_group: bool,
+    /// Reference scaled rate of each symbol to report the deviation from, where 0 skips it
+    pub reference_rates: Vec<u64>,
+    /// Reports each successful rate's deviation from its reference rate in `deviation_bps`
+    pub report_deviation: bool,
+    /// Symbols requested by their index into the sorted symbol table, after those in `symbols`
+    pub symbol_indices: Vec<u16>,
+    /// Aggregation methods to also report each symbol's rate by in `method_rates`, as
+    /// `AggregationMethod` codes
+    pub methods: Vec<AggregationMethodCode>,
+    /// Symbols to price every symbol against, reporting one response per pair named like
+    /// `"WBTC/WETH"`. Takes the place of `quote_symbol` when not empty
+    pub quote_symbols: Vec<String>,
 }
 
-#[derive(PartialEq, Debug)]
-enum ResponseCode {
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum ResponseCode {
     Success,
     SymbolNotSupported,
     NotEnoughSources,
     ConversionError,
+    UnsupportedAggregationMethod,
+    StaleData,
+    ParseError,
+    TooManySymbols,
+    SourceDisagreement,
+    LowParticipation,
+    Overflow,
+    InvalidNumber,
+    UnsupportedRoundingMode,
+    EmptyRequest,
+    PriceJump,
+    BatchRejected,
+    Degraded,
+    UnsupportedEvenRule,
+    SourceEmpty,
+    InvalidMaxAge,
+    SuccessWithWarning,
+    /// Health of a data source, with the number of validators that returned parseable data as
+    /// the source count
+    Health,
+    NegativeRate,
+    /// No source rates were left to aggregate
+    EmptyData,
     Unknown = 127,
 }
 
+impl From<u8> for ResponseCode {
+    fn from(code: u8) -> Self {
+        match code {
+            0 => ResponseCode::Success,
+            1 => ResponseCode::SymbolNotSupported,
+            2 => ResponseCode::NotEnoughSources,
+            3 => ResponseCode::ConversionError,
+            4 => ResponseCode::UnsupportedAggregationMethod,
+            5 => ResponseCode::StaleData,
+            6 => ResponseCode::ParseError,
+            7 => ResponseCode::TooManySymbols,
+            8 => ResponseCode::SourceDisagreement,
+            9 => ResponseCode::LowParticipation,
+            10 => ResponseCode::Overflow,
+            11 => ResponseCode::InvalidNumber,
+            12 => ResponseCode::UnsupportedRoundingMode,
+            13 => ResponseCode::EmptyRequest,
+            14 => ResponseCode::PriceJump,
+            15 => ResponseCode::BatchRejected,
+            16 => ResponseCode::Degraded,
+            17 => ResponseCode::UnsupportedEvenRule,
+            18 => ResponseCode::SourceEmpty,
+            19 => ResponseCode::InvalidMaxAge,
+            20 => ResponseCode::SuccessWithWarning,
+            21 => ResponseCode::Health,
+            22 => ResponseCode::NegativeRate,
+            23 => ResponseCode::EmptyData,
+            _ => ResponseCode::Unknown,
+        }
+    }
+}
+
+impl ResponseCode {
+    /// Returns how severe a failure the code represents, with `Success` being the least severe
+    fn severity(&self) -> u8 {
+        match self {
+            ResponseCode::Success | ResponseCode::Health => 0,
+            ResponseCode::SuccessWithWarning => 1,
+            ResponseCode::Degraded => 2,
+            ResponseCode::StaleData => 3,
+            ResponseCode::LowParticipation => 4,
+            ResponseCode::NotEnoughSources => 5,
+            ResponseCode::SourceEmpty => 6,
+            ResponseCode::EmptyData => 7,
+            ResponseCode::SymbolNotSupported => 8,
+            ResponseCode::ParseError => 9,
+            ResponseCode::SourceDisagreement => 10,
+            ResponseCode::PriceJump => 11,
+            ResponseCode::ConversionError => 12,
+            ResponseCode::NegativeRate => 13,
+            ResponseCode::Overflow => 14,
+            ResponseCode::InvalidNumber => 15,
+            ResponseCode::UnsupportedAggregationMethod => 16,
+            ResponseCode::UnsupportedRoundingMode => 17,
+            ResponseCode::UnsupportedEvenRule => 18,
+            ResponseCode::InvalidMaxAge => 19,
+            ResponseCode::TooManySymbols => 20,
+            ResponseCode::EmptyRequest => 21,
+            ResponseCode::BatchRejected => 22,
+            ResponseCode::Unknown => 23,
+        }
+    }
+
+    /// Returns whether the code leaves no usable rate, so warnings that still carry one such as
+    /// `Degraded` are not failures
+    fn is_failure(&self) -> bool {
+        self.severity() > ResponseCode::Degraded.severity()
+    }
+}
+
 #[derive(OBIEncode, OBISchema, PartialEq, Debug)]
-struct Response {
+pub struct Response {
     symbol: String,
     response_code: u8,
     rate: u64,
+    source_count: u8,
+    spread: u64,
+    sources_requested: u8,
+    chain_id: u8,
+    reason: String,
+    secondary_rate: u64,
+    std_dev: u64,
+    /// Ids of the symbol's data sources that produced no usable value, only reported on request
+    missing_sources: Vec<u32>,
+    /// Rate as a decimal string with one digit per decimal place of the symbol's multiplier,
+    /// e.g. `"1.234000000"`, only reported on request and left empty if the symbol failed
+    rate_str: String,
+    /// Decimal places `rate` is scaled by, i.e. the rate is `rate / 10^decimals`
+    decimals: u8,
+    /// Signed deviation of `rate` from the symbol's reference rate in basis points, only
+    /// reported on request and left 0 without a reference rate
+    deviation_bps: i32,
+    /// Rate aggregated by each of the requested methods in order, where 0 marks a method that
+    /// failed, only reported on request
+    method_rates: Vec<u64>,
 }
 
 impl Response {
-    fn new(symbol: String, response_code: ResponseCode, rate: u64) -> Self {
+    /// Creates a response with only its core fields set
+    pub fn new(symbol: String, response_code: ResponseCode, rate: u64, source_count: u8) -> Self {
+        // Number of data sources the symbol can be priced from, regardless of how many responded
+        let sources_requested = get_source_count(&symbol) as u8;
+        let decimals = canonicalize_symbol(&symbol).map_or(DEFAULT_DECIMALS, get_decimals) as u8;
         Response {
             symbol,
             response_code: response_code as u8,
             rate,
+            source_count,
+            spread: 0,
+            sources_requested,
+            chain_id: Chain::Mixed as u8,
+            reason: String::new(),
+            secondary_rate: 0,
+            std_dev: 0,
+            missing_sources: Vec::new(),
+            rate_str: String::new(),
+            decimals,
+            deviation_bps: 0,
+            method_rates: Vec::new(),
+        }
+    }
+
+    /// Returns the symbol as requested
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Returns the response code
+    pub fn code(&self) -> ResponseCode {
+        ResponseCode::from(self.response_code)
+    }
+
+    /// Returns the rate scaled by `10^decimals`
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    /// Returns the number of data sources the rate was aggregated from
+    pub fn source_count(&self) -> u8 {
+        self.source_count
+    }
+
+    /// Returns the number of decimal places the rate is scaled by
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    fn with_spread(mut self, spread: u64) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    fn with_chain_id(mut self, chain: Chain) -> Self {
+        self.chain_id = chain as u8;
+        self
+    }
+
+    fn with_reason(mut self, reason: String) -> Self {
+        self.reason = reason;
+        self
+    }
+
+    fn with_secondary_rate(mut self, secondary_rate: u64) -> Self {
+        self.secondary_rate = secondary_rate;
+        self
+    }
+
+    fn with_std_dev(mut self, std_dev: u64) -> Self {
+        self.std_dev = std_dev;
+        self
+    }
+
+    fn with_missing_sources(mut self, missing_sources: Vec<u32>) -> Self {
+        self.missing_sources = missing_sources;
+        self
+    }
+
+    fn with_rate_str(mut self, rate_str: String) -> Self {
+        self.rate_str = rate_str;
+        self
+    }
+
+    fn with_deviation_bps(mut self, deviation_bps: i32) -> Self {
+        self.deviation_bps = deviation_bps;
+        self
+    }
+
+    fn with_method_rates(mut self, method_rates: Vec<u64>) -> Self {
+        self.method_rates = method_rates;
+        self
+    }
+}
+
+/// Response carrying its rate as a u128, encoded by OBI as 16 big-endian bytes so rates scaled by
+/// a large multiplier such as `1e18` do not overflow
+#[derive(OBIEncode, OBISchema, PartialEq, Debug)]
+pub struct HighPrecisionResponse {
+    symbol: String,
+    response_code: u8,
+    rate: u128,
+}
+
+impl HighPrecisionResponse {
+    fn new(symbol: String, response_code: ResponseCode, rate: u128) -> Self {
+        HighPrecisionResponse {
+            symbol,
+            response_code: response_code as u8,
+            rate,
         }
     }
 }
 
+/// Result of an execution, which callers can also build and inspect
+///
+/// ```
+/// use dex_source_os::{Output, Response, ResponseCode};
+///
+/// let response = Response::new("WBTC".to_string(), ResponseCode::Success, 30000000000, 3);
+/// let output = Output::new(vec![response], Vec::new(), 1700000000);
+/// assert_eq!(output.batch_code(), ResponseCode::Success);
+/// assert_eq!(output.resolve_time(), 1700000000);
+///
+/// let response = &output.responses()[0];
+/// assert_eq!(response.symbol(), "WBTC");
+/// assert_eq!(response.code(), ResponseCode::Success);
+/// assert_eq!(response.rate(), 30000000000);
+/// assert_eq!(response.decimals(), 6);
+/// ```
 #[derive(OBIEncode, OBISchema, PartialEq, Debug)]
-struct Output {
+pub struct Output {
     responses: Vec<Response>,
+    debug_rates: Vec<Vec<u64>>,
+    batch_code: u8,
+    resolve_time: u64,
+    /// High precision counterpart of each response, only included on request
+    high_precision_responses: Vec<HighPrecisionResponse>,
+    /// Whether optional fields were dropped to keep the encoded output within the size limit
+    truncated: bool,
+}
+
+impl Output {
+    /// Creates an output of the responses, deriving its batch code from them
+    pub fn new(responses: Vec<Response>, debug_rates: Vec<Vec<u64>>, resolve_time: u64) -> Self {
+        let batch_code = get_batch_code(&responses) as u8;
+        Output {
+            responses,
+            debug_rates,
+            batch_code,
+            resolve_time,
+            high_precision_responses: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    /// Returns the response of every requested symbol
+    pub fn responses(&self) -> &[Response] {
+        &self.responses
+    }
+
+    /// Returns the most severe response code of the responses
+    pub fn batch_code(&self) -> ResponseCode {
+        ResponseCode::from(self.batch_code)
+    }
+
+    /// Returns the time the request was resolved at
+    pub fn resolve_time(&self) -> u64 {
+        self.resolve_time
+    }
+
+    fn with_high_precision_responses(
+        mut self,
+        high_precision_responses: Vec<HighPrecisionResponse>,
+    ) -> Self {
+        self.high_precision_responses = high_precision_responses;
+        self
+    }
+
+    /// Returns the number of bytes the output encodes to
+    fn encoded_len(&self) -> usize {
+        self.try_to_vec()
+            .map_or(usize::MAX, |encoded| encoded.len())
+    }
+
+    /// Drops the debug rates and then the failure details until the encoded output fits within
+    /// `max_bytes`. The core rate fields are always kept, even if they alone exceed the limit
+    fn truncated_to(mut self, max_bytes: usize) -> Self {
+        if self.encoded_len() > max_bytes && !self.debug_rates.is_empty() {
+            self.debug_rates.clear();
+            self.truncated = true;
+        }
+        if self.encoded_len() > max_bytes {
+            for response in self.responses.iter_mut() {
+                self.truncated |=
+                    !response.reason.is_empty() || !response.missing_sources.is_empty();
+                response.reason.clear();
+                response.missing_sources.clear();
+            }
+        }
+        self
+    }
+}
+
+/// Environment the oracle script executes in, abstracted so the execution can be driven
+/// without the owasm runtime
+pub trait OracleEnv {
+    /// Returns the minimum number of validators required to report
+    fn min_count(&self) -> i64;
+    /// Returns the number of validators asked to report
+    fn ask_count(&self) -> i64;
+    /// Returns the block time the request is executed at
+    fn execute_time(&self) -> i64;
+    /// Returns the raw outputs the validators reported for the external data id
+    fn load_input(&self, external_id: i64) -> Vec<String>;
+}
+
+/// Environment backed by the owasm runtime
+#[cfg(not(feature = "lib-only"))]
+struct OwasmEnv;
+
+#[cfg(not(feature = "lib-only"))]
+impl OracleEnv for OwasmEnv {
+    fn min_count(&self) -> i64 {
+        oei::get_min_count()
+    }
+
+    fn ask_count(&self) -> i64 {
+        oei::get_ask_count()
+    }
+
+    fn execute_time(&self) -> i64 {
+        oei::get_execute_time()
+    }
+
+    fn load_input(&self, external_id: i64) -> Vec<String> {
+        ext::load_input::<String>(external_id).collect()
+    }
+}
+
+/// Returns the most severe response code in the batch, or `Success` if every response succeeded
+fn get_batch_code(responses: &[Response]) -> ResponseCode {
+    responses
+        .iter()
+        .map(|r| ResponseCode::from(r.response_code))
+        .max_by_key(ResponseCode::severity)
+        .unwrap_or(ResponseCode::Success)
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-enum DataSources {
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DataSources {
     DS1INCHETH = 715,
     DSARKENETH = 716,
     DS1INCHBSC = 717,
     DSARKENBSC = 718,
+    DSUNISWAPETH = 719,
+}
+
+/// Chain family a price was sourced from
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Chain {
+    Mixed = 0,
+    Eth = 1,
+    Bsc = 2,
+}
+
+impl DataSources {
+    /// Returns the weight given to this data source when aggregating across sources
+    fn weight(&self) -> u32 {
+        match self {
+            DataSources::DS1INCHETH => 1,
+            DataSources::DSARKENETH => 1,
+            DataSources::DS1INCHBSC => 1,
+            DataSources::DSARKENBSC => 1,
+            DataSources::DSUNISWAPETH => 1,
+        }
+    }
+
+    /// Returns the chain family this data source reads from
+    fn chain(&self) -> Chain {
+        match self {
+            DataSources::DS1INCHETH | DataSources::DSARKENETH | DataSources::DSUNISWAPETH => {
+                Chain::Eth
+            }
+            DataSources::DS1INCHBSC | DataSources::DSARKENBSC => Chain::Bsc,
+        }
+    }
+}
+
+/// Configuration of a supported symbol
+struct SymbolConfig {
+    /// Data sources the symbol is requested from
+    sources: &'static [DataSources],
+    /// Minimum number of sources the symbol requires regardless of the caller's threshold
+    min_sources: u8,
+    /// Aggregation method the symbol uses unless the caller overrides it
+    method: Option<AggregationMethod>,
+}
+
+static SYMBOLS: phf::Map<&'static str, SymbolConfig> = phf_map! {
+    "WBTC" => SymbolConfig {
+        sources: &[
+            DataSources::DS1INCHETH,
+            DataSources::DSARKENETH,
+            DataSources::DSUNISWAPETH,
+        ],
+        min_sources: 2,
+        method: None,
+    },
+    "stETH" => SymbolConfig {
+        sources: &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
+        min_sources: 0,
+        method: None,
+    },
+    "wstETH" => SymbolConfig {
+        sources: &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
+        min_sources: 0,
+        method: None,
+    },
+    "WETH" => SymbolConfig {
+        sources: &[
+            DataSources::DS1INCHETH,
+            DataSources::DSARKENETH,
+            DataSources::DSUNISWAPETH,
+        ],
+        min_sources: 0,
+        method: None,
+    },
+    "XOR" => SymbolConfig {
+        sources: &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
+        min_sources: 0,
+        method: None,
+    },
+    "RLB" => SymbolConfig {
+        sources: &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
+        min_sources: 0,
+        method: Some(AggregationMethod::TrimmedMean),
+    },
+    "VAL" => SymbolConfig {
+        sources: &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
+        min_sources: 0,
+        method: None,
+    },
+    "PSWAP" => SymbolConfig {
+        sources: &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
+        min_sources: 0,
+        method: None,
+    },
+    "XST" => SymbolConfig {
+        sources: &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
+        min_sources: 0,
+        method: None,
+    },
+    "MUTE" => SymbolConfig {
+        sources: &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
+        min_sources: 0,
+        method: None,
+    },
+    "VC" => SymbolConfig {
+        sources: &[DataSources::DS1INCHBSC],
+        min_sources: 0,
+        method: None,
+    },
+    "MTRG" => SymbolConfig {
+        sources: &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
+        min_sources: 0,
+        method: None,
+    },
+    "PHB" => SymbolConfig {
+        sources: &[DataSources::DS1INCHBSC, DataSources::DSARKENBSC],
+        min_sources: 0,
+        method: None,
+    },
+    "BETH" => SymbolConfig {
+        sources: &[DataSources::DS1INCHBSC, DataSources::DSARKENBSC],
+        min_sources: 0,
+        method: None,
+    },
+};
+
+/// Returns the number of data sources a symbol or alias is requested from
+fn get_source_count(symbol: &str) -> usize {
+    get_sources(symbol).len()
+}
+
+/// Returns the data sources a symbol is configured with, or none if it is not supported
+fn get_sources(symbol: &str) -> &'static [DataSources] {
+    canonicalize_symbol(symbol).map_or(&[], |canonical| SYMBOLS[canonical].sources)
+}
+
+/// Returns the minimum source count a symbol declares for itself, or 0 if it is not supported
+fn get_declared_minimum_source_count(symbol: &str) -> usize {
+    SYMBOLS
+        .get(symbol)
+        .map_or(0, |config| config.min_sources as usize)
+}
+
+/// Returns the aggregation method a symbol declares for itself, if any
+fn get_declared_aggregation_method(symbol: &str) -> Option<AggregationMethod> {
+    SYMBOLS.get(symbol).and_then(|config| config.method)
 }
 
-static SYMBOLS: phf::Map<&'static str, &'static [DataSources]> = phf_map! {
-    "WBTC" => &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
-    "stETH" => &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
-    "wstETH" => &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
-    "WETH" => &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
-    "XOR" => &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
-    "RLB" => &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
-    "VAL" => &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
-    "PSWAP" => &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
-    "XST" => &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
-    "MUTE" => &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
-    "VC" => &[DataSources::DS1INCHBSC],
-    "MTRG" => &[DataSources::DS1INCHETH, DataSources::DSARKENETH],
-    "PHB" => &[DataSources::DS1INCHBSC, DataSources::DSARKENBSC],
-    "BETH" => &[DataSources::DS1INCHBSC, DataSources::DSARKENBSC],
+/// Decimal places used to scale each symbol's rate. Symbols not listed use `DEFAULT_DECIMALS`
+static DECIMALS: phf::Map<&'static str, u32> = phf_map! {
+    "WBTC" => 6,
+    "PSWAP" => 18,
+    "XST" => 18,
+};
+
+/// Alternative names resolving to a symbol in SYMBOLS. Aliases are a single hop and must not
+/// point at other aliases
+static ALIASES: phf::Map<&'static str, &'static str> = phf_map! {
+    "BTC" => "WBTC",
+    "ETH" => "WETH",
 };
 
-/// Returns a HashMap mapping the data source id to its supported symbols
-fn get_symbols_for_data_sources(symbols: &[String]) -> HashMap<i64, Vec<String>> {
-    symbols.iter().fold(
+/// Related symbols whose rates must keep a plausible ratio to each other
+struct SymbolGroup {
+    /// Symbol whose rate is checked
+    symbol: &'static str,
+    /// Symbol the rate is checked against
+    reference: &'static str,
+    /// Lowest plausible ratio of the symbol's rate to the reference's rate
+    min_ratio: f64,
+    /// Highest plausible ratio of the symbol's rate to the reference's rate
+    max_ratio: f64,
+}
+
+/// Equivalence groups cross-checked on request. Staked ETH trades close to ETH, while wrapped
+/// staked ETH accrues staking rewards and so is never worth less than staked ETH
+static GROUPS: &[SymbolGroup] = &[
+    SymbolGroup {
+        symbol: "stETH",
+        reference: "WETH",
+        min_ratio: 0.9,
+        max_ratio: 1.1,
+    },
+    SymbolGroup {
+        symbol: "wstETH",
+        reference: "stETH",
+        min_ratio: 1.0,
+        max_ratio: 2.0,
+    },
+];
+
+/// Finds the key of a map matching the symbol, preferring an exact match over a
+/// case-insensitive one
+fn find_key<V>(map: &'static phf::Map<&'static str, V>, symbol: &str) -> Option<&'static str> {
+    if let Some((key, _)) = map.get_entry(symbol) {
+        return Some(key);
+    }
+    map.keys()
+        .find(|key| key.eq_ignore_ascii_case(symbol))
+        .copied()
+}
+
+/// Checks that a symbol has no whitespace or control characters, which would corrupt the
+/// space-separated symbols of a data source request, and is no longer than `MAX_SYMBOL_LEN`
+fn is_well_formed_symbol(symbol: &str) -> bool {
+    symbol.len() <= MAX_SYMBOL_LEN && !symbol.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Resolves a symbol or alias case-insensitively to its canonical casing in SYMBOLS. Malformed
+/// symbols are never resolved
+fn canonicalize_symbol(symbol: &str) -> Option<&'static str> {
+    if !is_well_formed_symbol(symbol) {
+        return None;
+    }
+    find_key(&SYMBOLS, symbol).or_else(|| find_key(&ALIASES, symbol).map(|alias| ALIASES[alias]))
+}
+
+/// Returns the name to echo back for a symbol, keeping the alias if one was requested
+fn get_display_symbol(symbol: &str) -> String {
+    find_key(&SYMBOLS, symbol)
+        .or_else(|| find_key(&ALIASES, symbol))
+        .map(String::from)
+        .unwrap_or_else(|| symbol.to_string())
+}
+
+/// Returns every supported symbol in sorted order. Symbol indices refer to this table, so they
+/// shift whenever a symbol is added or removed
+fn get_symbol_table() -> Vec<&'static str> {
+    let mut symbols = SYMBOLS.keys().copied().collect::<Vec<&'static str>>();
+    symbols.sort();
+    symbols
+}
+
+/// Expands a request for the `ALL_SYMBOLS` sentinel into every supported symbol in sorted order
+fn expand_symbols(symbols: &[String]) -> Vec<String> {
+    if symbols.len() == 1 && symbols[0] == ALL_SYMBOLS {
+        get_symbol_table().into_iter().map(String::from).collect()
+    } else {
+        symbols.to_vec()
+    }
+}
+
+/// Decodes indices into the symbol table to their symbols. An out of range index decodes to
+/// `#<index>`, which no symbol matches, so it is reported as `SymbolNotSupported`
+fn decode_symbol_indices(indices: &[u16]) -> Vec<String> {
+    let table = get_symbol_table();
+    indices
+        .iter()
+        .map(|index| match table.get(*index as usize) {
+            Some(symbol) => symbol.to_string(),
+            None => format!("#{}", index),
+        })
+        .collect()
+}
+
+/// Returns the symbols the input requests, expanding `ALL_SYMBOLS` and appending the symbols
+/// requested by index
+fn get_input_symbols(input: &Input) -> Vec<String> {
+    let mut symbols = expand_symbols(&input.symbols);
+    symbols.extend(decode_symbol_indices(&input.symbol_indices));
+    symbols
+}
+
+/// Checks whether the mask selects the data source for the symbol, where bit `i` selects the
+/// symbol's `i`th configured source and a zero mask selects every source
+fn is_source_selected(symbol: &str, ds: DataSources, source_mask: u8) -> bool {
+    source_mask == 0
+        || canonicalize_symbol(symbol).is_some_and(|canonical| {
+            SYMBOLS[canonical]
+                .sources
+                .iter()
+                .position(|source| *source == ds)
+                .is_some_and(|i| i < 8 && source_mask & (1 << i) != 0)
+        })
+}
+
+/// Returns a HashMap mapping the data source to its supported symbols, requesting each symbol
+/// only from the sources its mask selects
+fn get_symbols_for_data_sources(
+    symbols: &[String],
+    source_masks: &[u8],
+) -> HashMap<DataSources, Vec<String>> {
+    symbols.iter().enumerate().fold(
         HashMap::with_capacity(DATA_SOURCE_COUNT),
-        |mut acc, symbol| {
-            if let Some(data_sources) = SYMBOLS.get(symbol.as_str()) {
-                for ds in *data_sources {
-                    acc.entry(*ds as i64)
+        |mut acc, (i, symbol)| {
+            if let Some(canonical) = canonicalize_symbol(symbol) {
+                let source_mask = source_masks.get(i).copied().unwrap_or(0);
+                for ds in SYMBOLS[canonical]
+                    .sources
+                    .iter()
+                    .filter(|ds| is_source_selected(canonical, **ds, source_mask))
+                {
+                    acc.entry(*ds)
                         .and_modify(|e| {
-                            e.push(symbol.clone());
+                            // Duplicated symbols are only requested once per data source
+                            if !e.iter().any(|s| s == canonical) {
+                                e.push(canonical.to_string());
+                            }
                         })
-                        .or_insert(vec![symbol.clone()]);
+                        .or_insert(vec![canonical.to_string()]);
                 }
             }
             acc
@@ -91,36 +792,261 @@ fn get_symbols_for_data_sources(symbols: &[String]) -> HashMap<i64, Vec<String>>
     )
 }
 
-/// Parses the individual values to assure its value is usable
+/// Returns the sorted symbols the data source with the id serves
+pub fn symbols_for_source(id: i64) -> Vec<&'static str> {
+    let mut symbols = SYMBOLS
+        .entries()
+        .filter(|(_, config)| config.sources.iter().any(|ds| *ds as i64 == id))
+        .map(|(symbol, _)| *symbol)
+        .collect::<Vec<&'static str>>();
+    symbols.sort();
+    symbols
+}
+
+/// Returns the number of comma-separated values the data source with the id returns for a
+/// request of the symbols, one for each of the symbols it serves
+pub fn expected_length(id: i64, symbols: &[String]) -> usize {
+    symbols
+        .iter()
+        .filter(|symbol| get_sources(symbol).iter().any(|ds| *ds as i64 == id))
+        .count()
+}
+
+/// Returns the data sources to request, sorted by id, along with their sorted symbols so the
+/// external data requests are issued in a deterministic order
+fn get_request_plan(symbols: &[String], source_masks: &[u8]) -> Vec<(DataSources, Vec<String>)> {
+    let mut plan = get_symbols_for_data_sources(symbols, source_masks)
+        .into_iter()
+        .map(|(ds, mut symbols)| {
+            symbols.sort();
+            (ds, symbols)
+        })
+        .collect::<Vec<(DataSources, Vec<String>)>>();
+    plan.sort_by_key(|(ds, _)| *ds as i64);
+    plan
+}
+
+/// Returns the data sources to request as their ids with their symbols, along with the symbols
+/// that no data source supports, without requesting anything. A data source's symbols may still
+/// be split over several external data requests if their calldata is too long
+pub fn plan_requests(
+    symbols: &[String],
+    source_masks: &[u8],
+) -> (Vec<(i64, Vec<String>)>, Vec<String>) {
+    let plan = get_request_plan(symbols, source_masks)
+        .into_iter()
+        .map(|(ds, symbols)| (ds as i64, symbols))
+        .collect();
+    let unsupported_symbols = symbols
+        .iter()
+        .filter(|symbol| canonicalize_symbol(symbol).is_none())
+        .cloned()
+        .collect();
+    (plan, unsupported_symbols)
+}
+
+/// Gets the number of decimal places the given symbol's rate is scaled by
+fn get_decimals(symbol: &str) -> u32 {
+    DECIMALS.get(symbol).copied().unwrap_or(DEFAULT_DECIMALS)
+}
+
+/// Gets the multiplier used to scale the given symbol's rate
+fn get_multiplier(symbol: &str) -> u64 {
+    10u64.pow(get_decimals(symbol))
+}
+
+/// Parses a value optionally carrying a traded volume as `price:volume`, e.g. `"1.23:1000"`
+fn validate_value_with_volume(v: &str) -> Result<(Option<f64>, Option<f64>)> {
+    match v.split_once(':') {
+        Some((price, volume)) => Ok((validate_value(price)?, validate_value(volume)?)),
+        None => Ok((validate_value(v)?, None)),
+    }
+}
+
+/// Parses the individual values to assure its value is usable. A value may be quoted as a
+/// `bid|ask` pair, e.g. `"1.20|1.24"`, in which case its mid price is used
 fn validate_value(v: &str) -> Result<Option<f64>> {
+    match v.split_once('|') {
+        Some((bid, ask)) => match (parse_value(bid)?, parse_value(ask)?) {
+            (Some(bid), Some(ask)) if ask < bid => bail!("Ask is below bid"),
+            (Some(bid), Some(ask)) => Ok(Some((bid + ask) / 2f64)),
+            _ => Ok(None),
+        },
+        None => parse_value(v),
+    }
+}
+
+/// Strips the thousand separators of a number, rejecting commas that do not split its integer
+/// part into groups of three digits
+fn strip_thousand_separators(v: &str) -> Result<String> {
+    if !v.contains(',') {
+        return Ok(v.to_string());
+    }
+    let (integer, fraction) = v.split_at(v.find(['.', 'e', 'E']).unwrap_or(v.len()));
+    let mut groups = integer.trim_start_matches(['+', '-']).split(',');
+    let first_group = groups.next().unwrap_or_default();
+    if fraction.contains(',')
+        || first_group.is_empty()
+        || first_group.len() > 3
+        || groups.any(|group| group.len() != 3)
+    {
+        bail!("Invalid thousand separator")
+    }
+    Ok(v.replace(',', ""))
+}
+
+/// Parses a single value, rejecting values that are not usable as a rate
+fn parse_value(v: &str) -> Result<Option<f64>> {
+    // Strips the double quotes some adapters wrap values in. A leading plus needs no handling as
+    // it is already accepted when parsing
+    let v = v
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(v);
     if v == "-" {
         Ok(None)
     } else {
-        let val = v.parse::<f64>()?;
-        if val < 0f64 {
+        // Strips a known unit suffix and thousand separators from human-formatted numbers. The
+        // separators only get here in quoted values, as unquoted commas delimit the fields
+        let v = UNIT_SUFFIXES
+            .iter()
+            .find_map(|unit| v.strip_suffix(unit))
+            .unwrap_or(v);
+        let val = strip_thousand_separators(v)?.parse::<f64>()?;
+        if !val.is_finite() {
+            bail!("Non-finite value")
+        }
+        // Normalizes negative zero, which compares equal to zero but keeps its sign
+        if val == 0f64 {
+            return Ok(Some(0f64));
+        }
+        if val < 0f64 || val.is_subnormal() {
             bail!("Invalid value")
         }
+        if val > MAX_REASONABLE_RATE {
+            bail!("Value exceeds maximum reasonable rate")
+        }
         Ok(Some(val))
     }
 }
 
-/// Validates and parses the a validator's data source output
-fn validate_and_parse_output(ds_output: &str, length: usize) -> Result<Vec<Option<f64>>> {
-    let parsed_output = ds_output
-        .split(",")
-        .map(|v| validate_value(v.trim()))
-        .collect::<Result<Vec<Option<f64>>>>()?;
+/// Splits a data source output into at most `limit` comma-separated fields, the last holding the
+/// rest of the output. Commas within double quotes do not delimit fields, so a quoted value may
+/// carry thousand separators, e.g. `"1,234.56"`
+fn split_fields(ds_output: &str, limit: usize) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in ds_output.char_indices() {
+        if fields.len() + 1 >= limit {
+            break;
+        }
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                fields.push(&ds_output[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(&ds_output[start..]);
+    fields
+}
+
+/// Validates and parses the a validator's data source output into each symbol's price, along
+/// with its volume if reported. An output may repeat the symbols over several rows, in which
+/// case each symbol takes the median of its values across the rows
+fn validate_and_parse_output(ds_output: &str, length: usize) -> Result<Observation> {
+    // Splits off at most one field more than the largest accepted output so oversized outputs
+    // are not fully scanned. The length saturates so an absurd one cannot overflow
+    let max_length = length.saturating_mul(MAX_OUTPUT_ROWS);
+    let fields = split_fields(ds_output, max_length.saturating_add(1));
 
-    // If the length of the parsed output is not equal to the expected length, raise an error
-    if parsed_output.len() != length {
+    // If the fields do not form whole rows of the expected length, raise an error
+    if length == 0 || !fields.len().is_multiple_of(length) || fields.len() > max_length {
         bail!("Mismatched length");
     }
 
-    Ok(parsed_output)
+    let values = fields
+        .into_iter()
+        .map(|v| validate_value_with_volume(v.trim()))
+        .collect::<Result<Vec<(Option<f64>, Option<f64>)>>>()?;
+    Ok(get_row_medians(values, length))
+}
+
+/// Reduces values laid out as consecutive rows of the given length to a single observation,
+/// each symbol taking the median of its prices and volumes across the rows
+fn get_row_medians(values: Vec<(Option<f64>, Option<f64>)>, length: usize) -> Observation {
+    if values.len() == length {
+        return values;
+    }
+    (0..length)
+        .map(|i| {
+            let (mut prices, mut volumes): (Vec<f64>, Vec<f64>) = values
+                .iter()
+                .skip(i)
+                .step_by(length)
+                .fold((vec![], vec![]), |mut acc, (price, volume)| {
+                    acc.0.extend(price);
+                    acc.1.extend(volume);
+                    acc
+                });
+            (median(&mut prices), median(&mut volumes))
+        })
+        .collect()
+}
+
+/// Parses a data source output into each symbol's price, rejecting malformed outputs with an
+/// error rather than a panic whatever the input, so it can be fuzzed directly
+pub fn parse_source_output(raw: &str, length: usize) -> Result<Vec<Option<f64>>> {
+    Ok(validate_and_parse_output(raw, length)?
+        .into_iter()
+        .map(|(price, _)| price)
+        .collect())
+}
+
+/// Fuzzing entry point taking raw bytes, where the first two bytes give the expected length and
+/// the rest is the output. Suits a `cargo fuzz` target, which only has to forward its data
+pub fn fuzz_parse_source_output(data: &[u8]) {
+    if let [high, low, raw @ ..] = data {
+        let length = u16::from_be_bytes([*high, *low]) as usize;
+        let _ = parse_source_output(&String::from_utf8_lossy(raw), length);
+    }
+}
+
+/// Splits a validator's report into its non-blank newline-separated rows
+fn split_report_rows(report: &str) -> Result<Vec<&str>> {
+    // Takes at most one row more than accepted so oversized reports are not fully scanned
+    let rows = report
+        .lines()
+        .filter(|row| !row.trim().is_empty())
+        .take(MAX_OUTPUT_ROWS + 1)
+        .collect::<Vec<&str>>();
+    if rows.len() > MAX_OUTPUT_ROWS {
+        bail!("Too many rows");
+    }
+    Ok(rows)
+}
+
+/// Parses each newline-separated row of a validator's report on its own as a data source output
+/// of the expected length, keeping the rows that parse along with the number of malformed rows
+/// discarded. Only a report with too many rows fails as a whole
+fn parse_report_rows(report: &str, length: usize) -> Result<(Vec<Observation>, usize)> {
+    let rows = split_report_rows(report)?;
+    let row_count = rows.len();
+    let observations = rows
+        .into_iter()
+        .filter_map(|row| validate_and_parse_output(row, length).ok())
+        .collect::<Vec<Observation>>();
+    let discarded_rows = row_count - observations.len();
+    Ok((observations, discarded_rows))
 }
 
-/// Gets the minimum successful response required given the minimum request count
+/// Gets the minimum successful response required given the minimum request count. A misconfigured
+/// minimum count below 1 is treated as 1, so this never returns 0
 fn get_minimum_response_count(min_count: i64) -> usize {
+    let min_count = min_count.max(1);
     if min_count.is_even() {
         ((min_count + 2) / 2) as usize
     } else {
@@ -128,120 +1054,571 @@ fn get_minimum_response_count(min_count: i64) -> usize {
     }
 }
 
-/// Filters and medianizes the parsed data source output
-fn filter_and_medianize(
-    rates: Vec<Vec<Option<f64>>>,
-    length: usize,
-    min_response: usize,
-) -> Vec<Option<f64>> {
-    (0..length)
-        .map(|i| {
-            let symbol_rates = rates.iter().filter_map(|o| o[i]).collect::<Vec<f64>>();
-            if symbol_rates.len() < min_response {
-                None
-            } else {
-                ext::stats::median_by(symbol_rates, ext::cmp::fcmp)
-            }
-        })
-        .collect::<Vec<Option<f64>>>()
+/// Checks whether a data source's outputs look stale, i.e. at least three validators, and more
+/// than the minimum response count, returned exactly the same output
+fn is_stale<T: PartialEq>(ds_outputs: &[T], min_response: usize) -> bool {
+    ds_outputs.len() >= 3
+        && ds_outputs.len() > min_response
+        && ds_outputs.iter().all(|o| *o == ds_outputs[0])
 }
 
-/// Aggregates the data sources outputs to either a result or error
-fn aggregate_value(rates: &[f64], minimum_source_count: usize) -> Result<u64, ResponseCode> {
-    if rates.len() < minimum_source_count {
-        Err(ResponseCode::NotEnoughSources)
-    } else {
-        if let Some(price) = ext::stats::median_by(rates.to_owned(), ext::cmp::fcmp) {
-            if let Some(mul_price) = u64::from_f64(price * MULTIPLIER as f64) {
-                Ok(mul_price)
-            } else {
-                Err(ResponseCode::ConversionError)
-            }
-        } else {
-            Err(ResponseCode::Unknown)
-        }
+/// Checks whether fewer than `MIN_PARTICIPATION_PERCENT` of the asked validators responded
+fn is_low_participation(ask_count: u64, response_count: usize) -> bool {
+    (response_count as u64).saturating_mul(100)
+        < ask_count.saturating_mul(MIN_PARTICIPATION_PERCENT)
+}
+
+/// Returns a `TooManySymbols` response for every symbol if more than `MAX_SYMBOLS` are requested
+fn reject_too_many_symbols(symbols: &[String]) -> Option<Vec<Response>> {
+    if symbols.len() <= MAX_SYMBOLS {
+        return None;
     }
+    Some(
+        symbols
+            .iter()
+            .map(|symbol| Response::new(symbol.clone(), ResponseCode::TooManySymbols, 0, 0))
+            .collect(),
+    )
 }
 
-/// Gets the oracle script responses
-fn get_responses(
-    symbols: &[String],
-    symbol_prices: HashMap<String, Vec<f64>>,
-    minimum_source_count: usize,
-) -> Vec<Response> {
-    symbols
+/// Returns an `InvalidMaxAge` response for every symbol if the maximum age exceeds
+/// `MAX_AGE_SECONDS`
+fn reject_invalid_max_age(symbols: &[String], max_age_seconds: u32) -> Option<Vec<Response>> {
+    if max_age_seconds <= MAX_AGE_SECONDS {
+        return None;
+    }
+    Some(
+        symbols
+            .iter()
+            .map(|symbol| Response::new(symbol.clone(), ResponseCode::InvalidMaxAge, 0, 0))
+            .collect(),
+    )
+}
+
+/// Returns a single `EmptyRequest` response if no symbols are requested
+fn reject_empty_request(symbols: &[String]) -> Option<Vec<Response>> {
+    if !symbols.is_empty() {
+        return None;
+    }
+    Some(vec![Response::new(
+        String::new(),
+        ResponseCode::EmptyRequest,
+        0,
+        0,
+    )])
+}
+
+/// Replaces every response with a `BatchRejected` one if any response failed, keeping only the
+/// symbol, source counts and reason. Responses with only a warning do not reject the batch
+fn reject_failed_batch(responses: Vec<Response>) -> Vec<Response> {
+    if !responses
         .iter()
-        .map(|symbol| {
-            if let Some(prices) = symbol_prices.get(symbol) {
-                match aggregate_value(&prices, minimum_source_count) {
-                    Ok(rate) => Response::new(symbol.clone(), ResponseCode::Success, rate),
-                    Err(code) => Response::new(symbol.clone(), code, 0),
-                }
-            } else {
-                Response::new(symbol.clone(), ResponseCode::SymbolNotSupported, 0)
-            }
+        .any(|r| ResponseCode::from(r.response_code).is_failure())
+    {
+        return responses;
+    }
+    responses
+        .into_iter()
+        .map(|response| Response {
+            response_code: ResponseCode::BatchRejected as u8,
+            rate: 0,
+            spread: 0,
+            secondary_rate: 0,
+            std_dev: 0,
+            rate_str: String::new(),
+            ..response
         })
         .collect()
 }
 
-fn prepare_impl(input: Input) {
-    for (id, symbols) in get_symbols_for_data_sources(&input.symbols) {
-        oei::ask_external_data(id, id, symbols.join(" ").as_bytes())
+/// Returns the symbols to request from the data sources, adding the quote symbol if it is missing
+fn get_requested_symbols(symbols: &[String], quote_symbol: &str) -> Vec<String> {
+    let mut requested_symbols = symbols.to_vec();
+    if !quote_symbol.is_empty() && !symbols.iter().any(|s| s == quote_symbol) {
+        requested_symbols.push(quote_symbol.to_string());
     }
+    requested_symbols
 }
 
-fn execute_impl(input: Input) -> Output {
-    // HashMap containing all symbols and a vector of their prices from each data source
-    let mut symbol_prices: HashMap<String, Vec<f64>> = HashMap::with_capacity(input.symbols.len());
+/// Rejects the request if it is empty, has too many symbols or an invalid maximum age. Both
+/// preparation and execution check the request here so they always agree on what is asked
+fn reject_request(symbols: &[String], input: &Input) -> Option<Vec<Response>> {
+    reject_empty_request(symbols)
+        .or_else(|| reject_too_many_symbols(symbols))
+        .or_else(|| reject_invalid_max_age(symbols, input.max_age_seconds))
+}
 
-    // Gets the minimum required response count
-    let min_resp_count = get_minimum_response_count(oei::get_min_count());
-
-    for (id, symbols) in get_symbols_for_data_sources(&input.symbols) {
-        // Parses the validator's responses from a raw string
-        let ds_outputs = ext::load_input::<String>(id)
-            .filter_map(|r| validate_and_parse_output(&r, symbols.len()).ok())
-            .collect::<Vec<Vec<Option<f64>>>>();
-
-        // Gets data source median rates
-        let median_rates = filter_and_medianize(ds_outputs, symbols.len(), min_resp_count);
-
-        // Saves symbol rates
-        for (symbol, opt_rate) in zip(symbols, median_rates) {
-            if let Some(rate) = opt_rate {
-                symbol_prices
-                    .entry(symbol)
-                    .and_modify(|e| e.push(rate))
-                    .or_insert(vec![rate]);
-            }
+/// Checks the whole input before execution, collecting every problem found instead of stopping
+/// at the first. Execution reports each of these through the failing symbols' response codes,
+/// so this is a pre-flight check for callers rather than a gate
+pub fn validate_input(input: &Input) -> Result<(), Vec<String>> {
+    let symbols = get_input_symbols(input);
+    let mut problems = Vec::new();
+
+    if symbols.is_empty() {
+        problems.push("no symbols requested".to_string());
+    } else if symbols.len() > MAX_SYMBOLS {
+        problems.push(format!(
+            "{} symbols requested, at most {} are allowed",
+            symbols.len(),
+            MAX_SYMBOLS
+        ));
+    }
+    if !input.skip_unsupported && !is_health_check(&symbols) {
+        for symbol in symbols.iter().filter(|s| canonicalize_symbol(s).is_none()) {
+            problems.push(format!("symbol {} is not supported", symbol));
+        }
+    }
+    let quote_symbols = std::iter::once(&input.quote_symbol).chain(input.quote_symbols.iter());
+    for quote_symbol in quote_symbols.filter(|s| !s.is_empty()) {
+        if canonicalize_symbol(quote_symbol).is_none() {
+            problems.push(format!("quote symbol {} is not supported", quote_symbol));
         }
     }
 
-    Output {
-        responses: get_responses(
-            &input.symbols,
-            symbol_prices,
-            input.minimum_source_count as usize,
-        ),
+    // Per-symbol vectors are matched to the symbols by index, so a longer one is never used
+    let per_symbol_lengths = [
+        ("minimum_source_counts", input.minimum_source_counts.len()),
+        ("invert", input.invert.len()),
+        ("previous_rates", input.previous_rates.len()),
+        ("source_masks", input.source_masks.len()),
+        ("reference_rates", input.reference_rates.len()),
+    ];
+    for (name, len) in per_symbol_lengths {
+        if len > symbols.len() {
+            problems.push(format!(
+                "{} has {} values for {} symbols",
+                name,
+                len,
+                symbols.len()
+            ));
+        }
     }
-}
 
-prepare_entry_point!(prepare_impl);
-execute_entry_point!(execute_impl);
+    // Absolute counts above the number of data sources can never be met
+    let minimum_source_counts = std::iter::once(&input.minimum_source_count)
+        .chain(input.minimum_source_counts.iter())
+        .map(|count| *count as usize);
+    for count in minimum_source_counts {
+        if count > DATA_SOURCE_COUNT && count <= PERCENTAGE_OFFSET {
+            problems.push(format!(
+                "minimum source count {} exceeds the {} data sources",
+                count, DATA_SOURCE_COUNT
+            ));
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    if AggregationMethod::try_from(input.aggregation_method.0).is_err() {
+        problems.push(format!(
+            "aggregation method {} is not supported",
+            input.aggregation_method.0
+        ));
+    }
+    for method in input.methods.iter() {
+        if AggregationMethod::try_from(method.0).is_err() {
+            problems.push(format!("method {} is not supported", method.0));
+        }
+    }
+    if RoundingMode::try_from(input.rounding.0).is_err() {
+        problems.push(format!(
+            "rounding mode {} is not supported",
+            input.rounding.0
+        ));
+    }
+    if EvenRule::try_from(input.even_rule.0).is_err() {
+        problems.push(format!("even rule {} is not supported", input.even_rule.0));
+    }
+    if input.max_age_seconds > MAX_AGE_SECONDS {
+        problems.push(format!(
+            "max age {} exceeds {} seconds",
+            input.max_age_seconds, MAX_AGE_SECONDS
+        ));
+    }
 
-    #[test]
-    fn test_validate_value() {
-        // Test normal case
-        let value = validate_value("0.12345").unwrap();
-        assert_eq!(value, Some(0.12345));
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
 
-        // Test null case
-        let null_value = validate_value("-").unwrap();
-        assert_eq!(null_value, None);
+/// Builds the calldata asking a data source for the symbols, passing on the maximum age of the
+/// data when one is set
+pub fn get_calldata(symbols: &[String], max_age_seconds: u32) -> String {
+    let calldata = symbols.join(" ");
+    if max_age_seconds == 0 {
+        calldata
+    } else {
+        format!("{} --max-age={}", calldata, max_age_seconds)
+    }
+}
+
+/// Splits the symbols into chunks whose calldata fits within `MAX_CALLDATA_BYTES`, keeping their
+/// order. A symbol too long to fit on its own still gets a chunk of its own
+fn split_symbols(symbols: Vec<String>, max_age_seconds: u32) -> Vec<Vec<String>> {
+    let suffix_len = get_calldata(&[], max_age_seconds).len();
+    let mut chunks: Vec<(Vec<String>, usize)> = Vec::new();
+    for symbol in symbols {
+        match chunks.last_mut() {
+            Some((chunk, len)) if *len + 1 + symbol.len() + suffix_len <= MAX_CALLDATA_BYTES => {
+                *len += 1 + symbol.len();
+                chunk.push(symbol);
+            }
+            _ => {
+                let len = symbol.len();
+                chunks.push((vec![symbol], len));
+            }
+        }
+    }
+    chunks.into_iter().map(|(chunk, _)| chunk).collect()
+}
+
+/// Returns the external data requests for the plan as their external ids, data sources and
+/// symbols. The first request of a data source uses its id as the external id, while each
+/// further request split off it is offset by another `EXTERNAL_ID_STRIDE`
+fn get_external_requests(
+    plan: Vec<(DataSources, Vec<String>)>,
+    max_age_seconds: u32,
+) -> Vec<(i64, DataSources, Vec<String>)> {
+    plan.into_iter()
+        .flat_map(|(ds, symbols)| {
+            split_symbols(symbols, max_age_seconds)
+                .into_iter()
+                .enumerate()
+                .map(move |(i, chunk)| (ds as i64 + i as i64 * EXTERNAL_ID_STRIDE, ds, chunk))
+        })
+        .collect()
+}
+
+/// Checks whether the request is a health check, i.e. asks for `HEALTH_SYMBOL`
+fn is_health_check(symbols: &[String]) -> bool {
+    symbols.iter().any(|symbol| symbol == HEALTH_SYMBOL)
+}
+
+/// Returns the external data requests for the symbols. A health check asks every data source for
+/// all of its symbols, ignoring the other symbols and the source masks
+fn get_input_requests(symbols: &[String], input: &Input) -> Vec<(i64, DataSources, Vec<String>)> {
+    let plan = if is_health_check(symbols) {
+        get_request_plan(&expand_symbols(&[ALL_SYMBOLS.to_string()]), &[])
+    } else {
+        let requested_symbols = input.quote_symbols.iter().fold(
+            get_requested_symbols(symbols, &input.quote_symbol),
+            |requested_symbols, quote_symbol| {
+                get_requested_symbols(&requested_symbols, quote_symbol)
+            },
+        );
+        get_request_plan(&requested_symbols, &input.source_masks)
+    };
+    get_external_requests(plan, input.max_age_seconds)
+}
+
+/// Returns a `Health` response for every data source, named by its id, counting the validators
+/// whose report has at least one parseable row, as execution would use it. A data source whose
+/// symbols were split over several requests counts the validators that returned parseable data
+/// to all of them
+fn get_health_responses(
+    requests: &[(i64, DataSources, Vec<String>)],
+    raw_outputs: &[Vec<String>],
+) -> Vec<Response> {
+    let mut counts: Vec<(DataSources, usize)> = Vec::new();
+    for ((_, ds, symbols), raw_outputs) in zip(requests, raw_outputs) {
+        let length = expected_length(*ds as i64, symbols);
+        let count = raw_outputs
+            .iter()
+            .filter(|raw| {
+                parse_report_rows(raw, length)
+                    .is_ok_and(|(observations, _)| !observations.is_empty())
+            })
+            .count();
+        match counts.iter_mut().find(|(counted, _)| counted == ds) {
+            Some((_, counted)) => *counted = (*counted).min(count),
+            None => counts.push((*ds, count)),
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(ds, count)| {
+            let count = u8::try_from(count).unwrap_or(u8::MAX);
+            Response::new((ds as i64).to_string(), ResponseCode::Health, 0, count)
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "lib-only"))]
+fn prepare_impl(input: Input) {
+    let symbols = get_input_symbols(&input);
+    if reject_request(&symbols, &input).is_some() {
+        return;
+    }
+    for (external_id, ds, symbols) in get_input_requests(&symbols, &input) {
+        let calldata = get_calldata(&symbols, input.max_age_seconds);
+        oei::ask_external_data(external_id, ds as i64, calldata.as_bytes())
+    }
+}
+
+/// Parses and medianizes a data source's raw outputs, saving the results to the source data
+fn collect_source_data(
+    source_data: &mut SourceData,
+    ds: DataSources,
+    symbols: Vec<String>,
+    raw_outputs: &[String],
+    min_resp_count: usize,
+    outlier_threshold: u8,
+    even_rule: EvenRule,
+) {
+    let length = expected_length(ds as i64, &symbols);
+    if !raw_outputs.is_empty() {
+        source_data.received_symbols.extend(symbols.iter().cloned());
+    }
+
+    // Flags the data source's symbols if every validator returned the same output
+    if is_stale(raw_outputs, min_resp_count) {
+        source_data.stale_symbols.extend(symbols.iter().cloned());
+    }
+
+    // Parses the validator's responses from a raw string, counting the malformed rows discarded.
+    // Each report is reduced to the median of its rows so a validator sending many rows still
+    // counts as a single observation
+    let mut discarded_rows = 0;
+    let ds_outputs = raw_outputs
+        .iter()
+        .filter_map(|r| parse_report_rows(r, length).ok())
+        .filter_map(|(observations, discarded)| {
+            discarded_rows += discarded;
+            (!observations.is_empty()).then(|| get_row_medians(observations.concat(), length))
+        })
+        .collect::<Vec<Observation>>();
+    if discarded_rows > 0 {
+        for symbol in symbols.iter() {
+            *source_data
+                .discarded_rows
+                .entry(symbol.clone())
+                .or_default() += discarded_rows;
+        }
+    }
+
+    if !ds_outputs.is_empty() {
+        source_data.parsed_symbols.extend(symbols.iter().cloned());
+    }
+
+    // Flags the data source's symbols if it responded but every parsed price was null
+    if !ds_outputs.is_empty()
+        && ds_outputs
+            .iter()
+            .flatten()
+            .all(|(price, _)| price.is_none())
+    {
+        source_data.empty_symbols.extend(symbols.iter().cloned());
+    }
+
+    // Splits each validator's output into its prices and volumes
+    let (price_outputs, volume_outputs): (Vec<_>, Vec<_>) = ds_outputs
+        .into_iter()
+        .map(|o| o.into_iter().unzip::<_, _, Vec<_>, Vec<_>>())
+        .unzip();
+
+    // Gets data source median rates and volumes
+    let median_rates = filter_and_medianize(
+        price_outputs,
+        length,
+        min_resp_count,
+        raw_outputs.len(),
+        outlier_threshold,
+        even_rule,
+        MissingPolicy::Drop,
+    );
+    let median_volumes = filter_and_medianize(
+        volume_outputs,
+        length,
+        min_resp_count,
+        raw_outputs.len(),
+        0,
+        EvenRule::Average,
+        MissingPolicy::Drop,
+    );
+
+    // Saves symbol rates, along with their volumes if reported
+    for (symbol, (opt_rate, opt_volume)) in zip(symbols, zip(median_rates, median_volumes)) {
+        if let Some(rate) = opt_rate {
+            if let Some(volume) = opt_volume {
+                source_data
+                    .volumes
+                    .entry(symbol.clone())
+                    .and_modify(|e| e.push((ds, volume)))
+                    .or_insert(vec![(ds, volume)]);
+            }
+            source_data
+                .prices
+                .entry(symbol)
+                .and_modify(|e| e.push((ds, rate)))
+                .or_insert(vec![(ds, rate)]);
+        }
+    }
+}
+
+/// Loads the raw outputs of every external data request in a single pass
+fn load_raw_outputs<E: OracleEnv>(
+    env: &E,
+    requests: &[(i64, DataSources, Vec<String>)],
+) -> Vec<Vec<String>> {
+    let mut raw_outputs = Vec::with_capacity(requests.len());
+    for (external_id, _, _) in requests {
+        raw_outputs.push(env.load_input(*external_id));
+    }
+    raw_outputs
+}
+
+/// Collects the source data of every data source in the plan from its loaded raw outputs
+fn collect_all_source_data(
+    plan: Vec<(DataSources, Vec<String>)>,
+    raw_outputs: &[Vec<String>],
+    min_resp_count: usize,
+    ask_count: u64,
+    outlier_threshold: u8,
+    even_rule: EvenRule,
+) -> SourceData {
+    // Prices and flags of all symbols collected from each data source
+    let mut source_data = SourceData::default();
+    for ((ds, symbols), raw_outputs) in zip(plan, raw_outputs) {
+        if is_low_participation(ask_count, raw_outputs.len()) {
+            source_data
+                .low_participation_symbols
+                .extend(symbols.iter().cloned());
+        }
+        collect_source_data(
+            &mut source_data,
+            ds,
+            symbols,
+            raw_outputs,
+            min_resp_count,
+            outlier_threshold,
+            even_rule,
+        );
+    }
+    source_data
+}
+
+/// Aggregates the rates of the requested symbols from the data source outputs of the environment
+pub fn execute_impl<E: OracleEnv>(env: &E, input: Input) -> Output {
+    let resolve_time = u64::try_from(env.execute_time()).unwrap_or(0);
+    let symbols = get_input_symbols(&input);
+    if let Some(responses) = reject_request(&symbols, &input) {
+        return Output::new(responses, Vec::new(), resolve_time);
+    }
+    if is_health_check(&symbols) {
+        let requests = get_input_requests(&symbols, &input);
+        let raw_outputs = load_raw_outputs(env, &requests);
+        let responses = get_health_responses(&requests, &raw_outputs);
+        return Output::new(responses, Vec::new(), resolve_time);
+    }
+
+    let even_rule = match EvenRule::try_from(input.even_rule.0) {
+        Ok(even_rule) => even_rule,
+        Err(code) => {
+            let responses = symbols
+                .iter()
+                .map(|symbol| Response::new(symbol.clone(), code, 0, 0))
+                .collect();
+            return Output::new(responses, Vec::new(), resolve_time);
+        }
+    };
+
+    // Gets the minimum required response count
+    let min_resp_count = get_minimum_response_count(env.min_count());
+    // A negative ask count would otherwise wrap to a huge one and flag every source
+    let ask_count = env.ask_count().max(0) as u64;
+
+    let requests = get_input_requests(&symbols, &input);
+    let raw_outputs = load_raw_outputs(env, &requests);
+    // Requests split off a data source carry disjoint symbols, so collecting each of them on its
+    // own reassembles the data source's rates
+    let source_data = collect_all_source_data(
+        requests
+            .into_iter()
+            .map(|(_, ds, symbols)| (ds, symbols))
+            .collect(),
+        &raw_outputs,
+        min_resp_count,
+        ask_count,
+        input.outlier_threshold,
+        even_rule,
+    );
+
+    let responses = get_responses(&symbols, &source_data, &input);
+    let responses = if input.strict {
+        reject_failed_batch(responses)
+    } else {
+        responses
+    };
+    // Per-source rates are only included on request to keep the output small
+    let debug_rates = if input.debug {
+        get_debug_rates(&symbols, &source_data, &input)
+    } else {
+        Vec::new()
+    };
+    let high_precision_responses = if input.high_precision {
+        get_high_precision_responses(&symbols, &source_data, &input, &responses)
+    } else {
+        Vec::new()
+    };
+    Output::new(responses, debug_rates, resolve_time)
+        .with_high_precision_responses(high_precision_responses)
+        .truncated_to(MAX_OUTPUT_BYTES)
+}
+
+#[cfg(not(feature = "lib-only"))]
+fn execute_owasm_impl(input: Input) -> Output {
+    execute_impl(&OwasmEnv, input)
+}
+
+#[cfg(not(feature = "lib-only"))]
+prepare_entry_point!(prepare_impl);
+#[cfg(not(feature = "lib-only"))]
+execute_entry_point!(execute_owasm_impl);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregation::{aggregate_value, AggregationParams};
+    use std::collections::HashSet;
+
+    /// Environment serving fixed data source outputs in place of the owasm runtime
+    #[derive(Default, Clone)]
+    struct MockEnv {
+        min_count: i64,
+        ask_count: i64,
+        /// Block time, or `None` to stub the block time call as unavailable, returning 0
+        execute_time: Option<i64>,
+        inputs: HashMap<i64, Vec<String>>,
+    }
+
+    impl OracleEnv for MockEnv {
+        fn min_count(&self) -> i64 {
+            self.min_count
+        }
+
+        fn ask_count(&self) -> i64 {
+            self.ask_count
+        }
+
+        fn execute_time(&self) -> i64 {
+            self.execute_time.unwrap_or(0)
+        }
+
+        fn load_input(&self, external_id: i64) -> Vec<String> {
+            self.inputs.get(&external_id).cloned().unwrap_or_default()
+        }
+    }
+
+    #[test]
+    fn test_validate_value() {
+        // Test normal case
+        let value = validate_value("0.12345").unwrap();
+        assert_eq!(value, Some(0.12345));
+
+        // Test null case
+        let null_value = validate_value("-").unwrap();
+        assert_eq!(null_value, None);
 
         // Test negative case
         let failed_value = validate_value("-0.555");
@@ -250,6 +1627,96 @@ mod tests {
         // Test failed case
         let failed_value = validate_value("abc");
         assert!(failed_value.is_err());
+
+        // Test scientific notation case
+        let value = validate_value("1.23e-4").unwrap();
+        assert_eq!(value, Some(0.000123));
+
+        // Test infinite case
+        let failed_value = validate_value("inf");
+        assert!(failed_value.is_err());
+
+        // Test NaN case
+        let failed_value = validate_value("NaN");
+        assert!(failed_value.is_err());
+
+        // Test unreasonably large case
+        let failed_value = validate_value("1e15");
+        assert!(failed_value.is_err());
+        let value = validate_value("1e12").unwrap();
+        assert_eq!(value, Some(1e12));
+
+        // Test thousand separator case
+        let value = validate_value("1,234.56").unwrap();
+        assert_eq!(value, Some(1234.56));
+        let failed_value = validate_value("1,2,3abc");
+        assert!(failed_value.is_err());
+
+        // Test unit suffix case
+        let value = validate_value("1.23USD").unwrap();
+        assert_eq!(value, Some(1.23));
+        let value = validate_value("1.23USDT").unwrap();
+        assert_eq!(value, Some(1.23));
+        let null_value = validate_value("-").unwrap();
+        assert_eq!(null_value, None);
+        let failed_value = validate_value("1.23xyz");
+        assert!(failed_value.is_err());
+
+        // Test negative zero case
+        let value = validate_value("-0.0").unwrap().unwrap();
+        assert_eq!(value, 0f64);
+        assert!(value.is_sign_positive());
+
+        // Test quoted case
+        let value = validate_value("\"1.23\"").unwrap();
+        assert_eq!(value, Some(1.23));
+        let null_value = validate_value("\"-\"").unwrap();
+        assert_eq!(null_value, None);
+        let failed_value = validate_value("\"1.23");
+        assert!(failed_value.is_err());
+
+        // Test leading plus case
+        let value = validate_value("+1.23").unwrap();
+        assert_eq!(value, Some(1.23));
+        let failed_value = validate_value("+-1.23");
+        assert!(failed_value.is_err());
+        let null_value = validate_value("-").unwrap();
+        assert_eq!(null_value, None);
+
+        // Test subnormal case
+        let failed_value = validate_value("1e-310");
+        assert!(failed_value.is_err());
+
+        // Test bid and ask case
+        let value = validate_value("1.20|1.24").unwrap().unwrap();
+        assert!((value - 1.22).abs() < 1e-12);
+        let value = validate_value("1.22").unwrap();
+        assert_eq!(value, Some(1.22));
+        let failed_value = validate_value("1.24|1.20");
+        assert!(failed_value.is_err());
+        let null_value = validate_value("-|1.24").unwrap();
+        assert_eq!(null_value, None);
+        let failed_value = validate_value("1.20|abc");
+        assert!(failed_value.is_err());
+    }
+
+    #[test]
+    fn test_validate_value_with_volume() {
+        // Test volume case
+        let value = validate_value_with_volume("1.23:1000").unwrap();
+        assert_eq!(value, (Some(1.23), Some(1000.0)));
+
+        // Test no volume case
+        let value = validate_value_with_volume("1.23").unwrap();
+        assert_eq!(value, (Some(1.23), None));
+
+        // Test null case
+        let value = validate_value_with_volume("-").unwrap();
+        assert_eq!(value, (None, None));
+
+        // Test invalid volume case
+        let value = validate_value_with_volume("1.23:abc");
+        assert!(value.is_err());
     }
 
     #[test]
@@ -257,25 +1724,203 @@ mod tests {
         // Test normal case
         let ds_outputs = "1.22,1.32,1.44".to_string();
         let parsed_output = validate_and_parse_output(&ds_outputs, 3).unwrap();
-        let expected_output = vec![Some(1.22), Some(1.32), Some(1.44)];
+        let expected_output = vec![(Some(1.22), None), (Some(1.32), None), (Some(1.44), None)];
         assert_eq!(parsed_output, expected_output);
 
         // Test normal bad format case
         let ds_outputs = "1.22, 1.32, 1.44".to_string();
         let parsed_output = validate_and_parse_output(&ds_outputs, 3).unwrap();
-        let expected_output = vec![Some(1.22), Some(1.32), Some(1.44)];
+        let expected_output = vec![(Some(1.22), None), (Some(1.32), None), (Some(1.44), None)];
         assert_eq!(parsed_output, expected_output);
 
         // Test contains null case
         let ds_outputs = "1.22,1.32,1.44,-,1.23".to_string();
         let parsed_output = validate_and_parse_output(&ds_outputs, 5).unwrap();
-        let expected_output = vec![Some(1.22), Some(1.32), Some(1.44), None, Some(1.23)];
+        let expected_output = vec![
+            (Some(1.22), None),
+            (Some(1.32), None),
+            (Some(1.44), None),
+            (None, None),
+            (Some(1.23), None),
+        ];
         assert_eq!(parsed_output, expected_output);
 
         // Test invalid case
         let ds_outputs = "NO_DATA,ERROR".to_string();
         let parsed_output = validate_and_parse_output(&ds_outputs, 2);
         assert!(parsed_output.is_err());
+
+        // Test volume case
+        let parsed_output = validate_and_parse_output("1.23:1000,-,1.5", 3).unwrap();
+        let expected_output = vec![(Some(1.23), Some(1000.0)), (None, None), (Some(1.5), None)];
+        assert_eq!(parsed_output, expected_output);
+        let parsed_output = validate_and_parse_output("1.23:abc,1.0,1.0", 3);
+        assert!(parsed_output.is_err());
+
+        // Test excessive field count case, where unparsable trailing fields are never parsed
+        let ds_outputs = vec!["1.0"; 3]
+            .into_iter()
+            .chain(vec!["abc"; 9997])
+            .collect::<Vec<&str>>()
+            .join(",");
+        let parsed_output = validate_and_parse_output(&ds_outputs, 3);
+        assert_eq!(parsed_output.unwrap_err().to_string(), "Mismatched length");
+
+        // Test too few fields case
+        let parsed_output = validate_and_parse_output("1.0,1.0", 3);
+        assert_eq!(parsed_output.unwrap_err().to_string(), "Mismatched length");
+
+        // Test multiple rows case
+        let parsed_output = validate_and_parse_output("1.0,2.0:10,1.2,-,1.1,2.2:30", 2).unwrap();
+        let expected_output = vec![(Some(1.1), None), (Some(2.1), Some(20.0))];
+        assert_eq!(parsed_output, expected_output);
+
+        // Test partial row case
+        let parsed_output = validate_and_parse_output("1.0,2.0,1.2,2.2", 3);
+        assert_eq!(parsed_output.unwrap_err().to_string(), "Mismatched length");
+
+        // Test too many rows case
+        let ds_outputs = vec!["1.0"; 2 * MAX_OUTPUT_ROWS + 2].join(",");
+        let parsed_output = validate_and_parse_output(&ds_outputs, 2);
+        assert_eq!(parsed_output.unwrap_err().to_string(), "Mismatched length");
+
+        // Test quoted thousand separator case
+        let parsed_output = validate_and_parse_output("\"1,234.56\", 2.0", 2).unwrap();
+        let expected_output = vec![(Some(1234.56), None), (Some(2.0), None)];
+        assert_eq!(parsed_output, expected_output);
+        let parsed_output = validate_and_parse_output("\"1,234.56\"", 1).unwrap();
+        assert_eq!(parsed_output, vec![(Some(1234.56), None)]);
+
+        // Test unquoted thousand separator case, where the comma delimits the fields
+        let parsed_output = validate_and_parse_output("1,234.56", 2).unwrap();
+        assert_eq!(parsed_output, vec![(Some(1.0), None), (Some(234.56), None)]);
+
+        let parsed_output = validate_and_parse_output("\"1,234,567\"", 1).unwrap();
+        assert_eq!(parsed_output, vec![(Some(1234567.0), None)]);
+
+        // Test misplaced thousand separator case
+        let parsed_output = validate_and_parse_output("\"1,2,3\"", 1);
+        assert_eq!(
+            parsed_output.unwrap_err().to_string(),
+            "Invalid thousand separator"
+        );
+        let parsed_output = validate_and_parse_output("\"12,34\"", 1);
+        assert_eq!(
+            parsed_output.unwrap_err().to_string(),
+            "Invalid thousand separator"
+        );
+        assert!(validate_and_parse_output("\"1234,567\"", 1).is_err());
+        assert!(validate_and_parse_output("\",123\"", 1).is_err());
+        assert!(validate_and_parse_output("\"1.234,5\"", 1).is_err());
+
+        // Test malformed quoted value case
+        assert!(validate_and_parse_output("\"1,2,3abc\"", 1).is_err());
+        assert!(validate_and_parse_output("\"1,234.56", 1).is_err());
+    }
+
+    #[test]
+    fn test_split_fields() {
+        // Test unquoted case
+        assert_eq!(split_fields("1.0,2.0,3.0", 10), vec!["1.0", "2.0", "3.0"]);
+
+        // Test quoted comma case
+        assert_eq!(split_fields("\"1,234\",2.0", 10), vec!["\"1,234\"", "2.0"]);
+
+        // Test limit case
+        assert_eq!(split_fields("1.0,2.0,3.0", 2), vec!["1.0", "2.0,3.0"]);
+        assert_eq!(split_fields("1.0,2.0", 1), vec!["1.0,2.0"]);
+
+        // Test empty case
+        assert_eq!(split_fields("", 10), vec![""]);
+    }
+
+    #[test]
+    fn test_parse_source_output() {
+        // Test normal case
+        let parsed_output = parse_source_output("1.5:1000,-", 2).unwrap();
+        assert_eq!(parsed_output, vec![Some(1.5), None]);
+
+        // Test empty fields between commas case
+        assert!(parse_source_output("1.5,,2.0", 3).is_err());
+        assert!(parse_source_output(",,", 3).is_err());
+        assert!(parse_source_output("", 1).is_err());
+
+        // Test very long decimal case
+        let long_decimal = format!("1.{}", "1".repeat(100000));
+        assert_eq!(
+            parse_source_output(&long_decimal, 1).unwrap(),
+            vec![Some(1.1111111111111112)]
+        );
+        let long_integer = "9".repeat(100000);
+        assert!(parse_source_output(&long_integer, 1).is_err());
+
+        // Test absurd length case
+        assert!(parse_source_output("1.0", usize::MAX).is_err());
+        assert!(parse_source_output("1.0", 0).is_err());
+
+        // Test embedded null and exotic unicode case
+        assert!(parse_source_output("1.0\0,2.0", 2).is_err());
+        assert!(parse_source_output("१.५,٢", 2).is_err());
+        assert!(parse_source_output("1.0\u{202e},\u{feff}2.0", 2).is_err());
+        assert!(parse_source_output("\"", 1).is_err());
+        assert!(parse_source_output("|:|", 1).is_err());
+    }
+
+    #[test]
+    fn test_fuzz_parse_source_output() {
+        // Test inputs that must not panic
+        let inputs: Vec<Vec<u8>> = vec![
+            vec![],
+            vec![0],
+            vec![0, 0],
+            vec![255, 255, b'1'],
+            [&[0, 3][..], b"1,2,3"].concat(),
+            [&[0, 1][..], &[0xff, 0xfe, 0x00]].concat(),
+            [&[0, 1][..], ",".repeat(100000).as_bytes()].concat(),
+        ];
+        for input in inputs {
+            fuzz_parse_source_output(&input);
+        }
+    }
+
+    #[test]
+    fn test_parse_report_rows() {
+        // Test single row case
+        let (observations, discarded_rows) = parse_report_rows("1.0,2.0", 2).unwrap();
+        assert_eq!(
+            observations,
+            vec![vec![(Some(1.0), None), (Some(2.0), None)]]
+        );
+        assert_eq!(discarded_rows, 0);
+
+        // Test all rows valid case
+        let (observations, discarded_rows) = parse_report_rows("1.0,2.0\n1.2,-\n", 2).unwrap();
+        assert_eq!(
+            observations,
+            vec![
+                vec![(Some(1.0), None), (Some(2.0), None)],
+                vec![(Some(1.2), None), (None, None)],
+            ]
+        );
+        assert_eq!(discarded_rows, 0);
+
+        // Test one good and one malformed row case
+        let (observations, discarded_rows) = parse_report_rows("1.0,2.0\n1.2", 2).unwrap();
+        assert_eq!(
+            observations,
+            vec![vec![(Some(1.0), None), (Some(2.0), None)]]
+        );
+        assert_eq!(discarded_rows, 1);
+
+        // Test all rows malformed case
+        let (observations, discarded_rows) = parse_report_rows("bad\n1.2", 2).unwrap();
+        assert!(observations.is_empty());
+        assert_eq!(discarded_rows, 2);
+
+        // Test too many rows case
+        let report = ["1.0"; MAX_OUTPUT_ROWS + 1].join("\n");
+        let parsed_report = parse_report_rows(&report, 1);
+        assert_eq!(parsed_report.unwrap_err().to_string(), "Too many rows");
     }
 
     #[test]
@@ -288,80 +1933,1610 @@ mod tests {
             .map(|x| get_minimum_response_count(x as i64))
             .collect::<Vec<usize>>();
         assert_eq!(min_resp_count, expected_min_responses);
+
+        // Test misconfigured minimum count case
+        assert_eq!(get_minimum_response_count(-5), 1);
+        assert_eq!(get_minimum_response_count(0), 1);
+        assert_eq!(get_minimum_response_count(1), 1);
+        assert_eq!(get_minimum_response_count(i64::MIN), 1);
     }
 
     #[test]
-    fn test_filter_and_medianize() {
-        // Test normal case
-        let rates = vec![
-            vec![Some(0.0), Some(1.3), Some(2.3)],
-            vec![Some(0.1), Some(1.0), Some(2.0)],
-            vec![Some(0.3), Some(1.1), Some(2.3)],
-            vec![Some(0.3), Some(1.1), Some(2.3)],
+    fn test_is_stale() {
+        // Test identical case
+        let ds_outputs = vec![
+            validate_and_parse_output("1.22,1.32", 2).unwrap(),
+            validate_and_parse_output("1.22,1.32", 2).unwrap(),
+            validate_and_parse_output("1.22,1.32", 2).unwrap(),
         ];
-        let result = filter_and_medianize(rates, 3, 2);
-        let expected_result = vec![Some(0.2), Some(1.1), Some(2.3)];
-        assert_eq!(result, expected_result);
-
-        // Test too many missing case
-        let rates = vec![
-            vec![Some(0.0), Some(1.3), None],
-            vec![Some(0.1), Some(1.0), None],
-            vec![Some(0.3), Some(1.1), None],
-            vec![Some(0.3), Some(1.1), Some(2.3)],
+        assert!(is_stale(&ds_outputs, 2));
+
+        // Test not more than minimum response count case
+        assert!(!is_stale(&ds_outputs, 3));
+
+        // Test too few responses case
+        assert!(!is_stale(&ds_outputs[..2], 1));
+
+        // Test differing case
+        let ds_outputs = vec![
+            validate_and_parse_output("1.22,1.32", 2).unwrap(),
+            validate_and_parse_output("1.22,1.32", 2).unwrap(),
+            validate_and_parse_output("1.22,1.33", 2).unwrap(),
         ];
-        let result = filter_and_medianize(rates, 3, 2);
-        let expected_result = vec![Some(0.2), Some(1.1), None];
-        assert_eq!(result, expected_result);
+        assert!(!is_stale(&ds_outputs, 2));
     }
 
     #[test]
-    fn test_aggregate_value() {
+    fn test_collect_source_data() {
         // Test normal case
-        let data = vec![1.23, 1.24, 1.25, 1.26, 1.27];
-        let normal_res = aggregate_value(&data, 3);
-        assert_eq!(normal_res.unwrap(), 1250000000);
+        let mut source_data = SourceData::default();
+        let symbols = vec!["WETH".to_string(), "XOR".to_string()];
+        let raw_outputs = vec!["2.25,1.0".to_string(), "2.75,1.5".to_string()];
+        collect_source_data(
+            &mut source_data,
+            DataSources::DS1INCHETH,
+            symbols,
+            &raw_outputs,
+            2,
+            0,
+            EvenRule::Average,
+        );
+        assert_eq!(
+            source_data.prices["WETH"],
+            vec![(DataSources::DS1INCHETH, 2.5)]
+        );
+        assert_eq!(
+            source_data.prices["XOR"],
+            vec![(DataSources::DS1INCHETH, 1.25)]
+        );
+        assert!(!source_data.is_unparsable("WETH"));
+        assert!(source_data.discarded_rows.is_empty());
 
-        // Test overflow case
-        let invalid_data = vec![f64::MAX, f64::MAX, f64::MAX, f64::MAX, f64::MAX];
-        let overflow_res = aggregate_value(&invalid_data, 3);
-        assert_eq!(overflow_res.unwrap_err(), ResponseCode::ConversionError);
+        // Test partially malformed report case
+        let mut source_data = SourceData::default();
+        let symbols = vec!["WETH".to_string(), "XOR".to_string()];
+        let raw_outputs = vec![
+            "2.25,1.0\n2.5,bad".to_string(),
+            "2.75,1.5".to_string(),
+            "oops".to_string(),
+        ];
+        collect_source_data(
+            &mut source_data,
+            DataSources::DS1INCHETH,
+            symbols,
+            &raw_outputs,
+            2,
+            0,
+            EvenRule::Average,
+        );
+        assert_eq!(
+            source_data.prices["WETH"],
+            vec![(DataSources::DS1INCHETH, 2.5)]
+        );
+        assert_eq!(source_data.discarded_rows["WETH"], 2);
+        assert_eq!(source_data.discarded_rows["XOR"], 2);
 
-        // Test underflow case
-        let invalid_data = vec![f64::MIN, f64::MIN, f64::MIN, f64::MIN, f64::MIN];
-        let overflow_res = aggregate_value(&invalid_data, 3);
-        assert_eq!(overflow_res.unwrap_err(), ResponseCode::ConversionError);
+        // Test many rows from a single validator case
+        let mut source_data = SourceData::default();
+        let symbols = vec!["XOR".to_string()];
+        let raw_outputs = vec![
+            ["9.0"; MAX_OUTPUT_ROWS].join("\n"),
+            "1.0".to_string(),
+            "1.0".to_string(),
+        ];
+        collect_source_data(
+            &mut source_data,
+            DataSources::DS1INCHETH,
+            symbols.clone(),
+            &raw_outputs,
+            2,
+            0,
+            EvenRule::Average,
+        );
+        assert_eq!(
+            source_data.prices["XOR"],
+            vec![(DataSources::DS1INCHETH, 1.0)]
+        );
 
-        // Test NaN case
-        let invalid_data = vec![f64::NAN, f64::NAN, f64::NAN, f64::NAN, f64::NAN];
-        let overflow_res = aggregate_value(&invalid_data, 3);
-        assert_eq!(overflow_res.unwrap_err(), ResponseCode::ConversionError);
+        // Test many rows from a single validator below the minimum response count case
+        let mut source_data = SourceData::default();
+        let raw_outputs = vec![
+            ["9.0"; MAX_OUTPUT_ROWS].join("\n"),
+            "bad".to_string(),
+            "bad".to_string(),
+        ];
+        collect_source_data(
+            &mut source_data,
+            DataSources::DS1INCHETH,
+            symbols,
+            &raw_outputs,
+            2,
+            0,
+            EvenRule::Average,
+        );
+        assert!(source_data.prices.is_empty());
 
-        // Test not enough sources case
-        let invalid_data = vec![];
-        let overflow_res = aggregate_value(&invalid_data, 3);
-        assert_eq!(overflow_res.unwrap_err(), ResponseCode::NotEnoughSources);
-    }
+        // Test unreasonably large value case
+        let mut source_data = SourceData::default();
+        let symbols = vec!["XOR".to_string()];
+        let raw_outputs = vec!["1.0".to_string(), "1e15".to_string(), "1.5".to_string()];
+        collect_source_data(
+            &mut source_data,
+            DataSources::DS1INCHETH,
+            symbols.clone(),
+            &raw_outputs,
+            2,
+            0,
+            EvenRule::Average,
+        );
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            responses[0],
+            Response::new("XOR".to_string(), ResponseCode::Success, 1250000000, 1)
+                .with_chain_id(Chain::Eth)
+        );
 
-    #[test]
-    fn test_get_responses() {
-        let symbols = vec!["BTC".to_string(), "ETH".to_string(), "DNE".to_string()];
-        let symbol_prices = HashMap::from([
-            (String::from("BTC"), vec![1.23, 1.24, 1.25, 1.26, 1.27]),
-            (String::from("ETH"), vec![2.31, 2.32]),
-        ]);
-        let responses = get_responses(&symbols, symbol_prices, 3);
+        // Test volume case
+        let mut source_data = SourceData::default();
+        let symbols = vec!["XOR".to_string()];
+        collect_source_data(
+            &mut source_data,
+            DataSources::DS1INCHETH,
+            symbols.clone(),
+            &["1.0:100".to_string(), "1.0:300".to_string()],
+            2,
+            0,
+            EvenRule::Average,
+        );
+        collect_source_data(
+            &mut source_data,
+            DataSources::DSARKENETH,
+            symbols.clone(),
+            &["2.0:600".to_string(), "2.0:600".to_string()],
+            2,
+            0,
+            EvenRule::Average,
+        );
+        assert_eq!(
+            source_data.volumes["XOR"],
+            vec![
+                (DataSources::DS1INCHETH, 200.0),
+                (DataSources::DSARKENETH, 600.0)
+            ]
+        );
+        let input = Input {
+            minimum_source_count: 2,
+            aggregation_method: AggregationMethodCode(4),
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(responses[0].rate, 1750000000);
+
+        // Test malformed outputs case
+        let mut source_data = SourceData::default();
+        let symbols = vec!["WETH".to_string()];
+        let raw_outputs = vec!["NO_DATA".to_string(), "ERROR".to_string()];
+        collect_source_data(
+            &mut source_data,
+            DataSources::DS1INCHETH,
+            symbols.clone(),
+            &raw_outputs,
+            2,
+            0,
+            EvenRule::Average,
+        );
+        assert!(source_data.prices.is_empty());
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                ..Default::default()
+            },
+        );
         assert_eq!(
             responses[0],
-            Response::new("BTC".to_string(), ResponseCode::Success, 1250000000)
+            Response::new("WETH".to_string(), ResponseCode::ParseError, 0, 0)
+        );
+
+        // Test no data case
+        let mut source_data = SourceData::default();
+        collect_source_data(
+            &mut source_data,
+            DataSources::DS1INCHETH,
+            symbols.clone(),
+            &[],
+            2,
+            0,
+            EvenRule::Average,
+        );
+        let responses = get_responses(
+            &symbols,
+            &source_data,
+            &Input {
+                minimum_source_count: 1,
+                ..Default::default()
+            },
         );
         assert_eq!(
-            responses[1],
-            Response::new("ETH".to_string(), ResponseCode::NotEnoughSources, 0)
+            responses[0],
+            Response::new("WETH".to_string(), ResponseCode::SymbolNotSupported, 0, 0)
         );
+    }
+
+    #[test]
+    fn test_canonicalize_symbol() {
+        // Test exact case
+        assert_eq!(canonicalize_symbol("WETH"), Some("WETH"));
+
+        // Test lowercase case
+        assert_eq!(canonicalize_symbol("weth"), Some("WETH"));
+
+        // Test mixed-case canonical case
+        assert_eq!(canonicalize_symbol("StETH"), Some("stETH"));
+        assert_eq!(canonicalize_symbol("WSTETH"), Some("wstETH"));
+
+        // Test unknown case
+        assert_eq!(canonicalize_symbol("foo"), None);
+
+        // Test alias case
+        assert_eq!(canonicalize_symbol("BTC"), Some("WBTC"));
+        assert_eq!(canonicalize_symbol("eth"), Some("WETH"));
+        assert_eq!(canonicalize_symbol("DOGE"), None);
+
+        // Test malformed case
+        assert_eq!(canonicalize_symbol("WB TC"), None);
+        assert_eq!(canonicalize_symbol("WBTC\u{0}"), None);
+
+        // Test too long case
+        assert_eq!(canonicalize_symbol(&"W".repeat(MAX_SYMBOL_LEN + 1)), None);
+        assert_eq!(canonicalize_symbol(&"WBTC".repeat(25)), None);
+
+        // Test single hop alias case
+        assert!(ALIASES.values().all(|target| SYMBOLS.contains_key(target)));
+    }
+
+    #[test]
+    fn test_get_display_symbol() {
+        // Test canonical case
+        assert_eq!(get_display_symbol("weth"), "WETH");
+
+        // Test alias case
+        assert_eq!(get_display_symbol("btc"), "BTC");
+
+        // Test unknown case
+        assert_eq!(get_display_symbol("foo"), "foo");
+    }
+
+    #[test]
+    fn test_expand_symbols() {
+        // Test sentinel case
+        let symbols = expand_symbols(&["*".to_string()]);
+        let mut expected_symbols = SYMBOLS.keys().map(|s| s.to_string()).collect::<Vec<_>>();
+        expected_symbols.sort();
+        assert_eq!(symbols, expected_symbols);
+        assert_eq!(symbols.len(), SYMBOLS.len());
+        assert!(symbols.windows(2).all(|w| w[0] < w[1]));
+
+        // Test regular case
+        let symbols = vec!["WETH".to_string(), "*".to_string()];
+        assert_eq!(expand_symbols(&symbols), symbols);
+    }
+
+    #[test]
+    fn test_decode_symbol_indices() {
+        let table = get_symbol_table();
+        assert_eq!(table.len(), SYMBOLS.len());
+        assert!(table.windows(2).all(|w| w[0] < w[1]));
+
+        // Test in range case
+        let wbtc = table.iter().position(|s| *s == "WBTC").unwrap() as u16;
+        let xor = table.iter().position(|s| *s == "XOR").unwrap() as u16;
         assert_eq!(
-            responses[2],
-            Response::new("DNE".to_string(), ResponseCode::SymbolNotSupported, 0)
+            decode_symbol_indices(&[xor, wbtc, 0]),
+            vec!["XOR".to_string(), "WBTC".to_string(), table[0].to_string()]
         );
+
+        // Test out of range case
+        let out_of_range = table.len() as u16;
+        let symbols = decode_symbol_indices(&[wbtc, out_of_range, u16::MAX]);
+        assert_eq!(
+            symbols,
+            vec![
+                "WBTC".to_string(),
+                format!("#{}", out_of_range),
+                "#65535".to_string()
+            ]
+        );
+        assert_eq!(canonicalize_symbol(&symbols[1]), None);
+
+        // Test empty case
+        assert!(decode_symbol_indices(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_get_input_symbols() {
+        let wbtc = get_symbol_table()
+            .iter()
+            .position(|s| *s == "WBTC")
+            .unwrap() as u16;
+
+        // Test symbols and indices case
+        let input = Input {
+            symbols: vec!["XOR".to_string()],
+            symbol_indices: vec![wbtc, u16::MAX],
+            ..Default::default()
+        };
+        assert_eq!(
+            get_input_symbols(&input),
+            vec!["XOR".to_string(), "WBTC".to_string(), "#65535".to_string()]
+        );
+
+        // Test indices only case
+        let input = Input {
+            symbol_indices: vec![wbtc],
+            ..Default::default()
+        };
+        assert_eq!(get_input_symbols(&input), vec!["WBTC".to_string()]);
+    }
+
+    #[test]
+    fn test_get_symbols_for_data_sources() {
+        let symbols = vec!["weth".to_string(), "StETH".to_string(), "foo".to_string()];
+        let symbols_for_data_sources = get_symbols_for_data_sources(&symbols, &[]);
+        let expected_symbols = vec!["WETH".to_string(), "stETH".to_string()];
+        assert_eq!(symbols_for_data_sources.len(), 3);
+        assert_eq!(
+            symbols_for_data_sources[&DataSources::DS1INCHETH],
+            expected_symbols
+        );
+        assert_eq!(
+            symbols_for_data_sources[&DataSources::DSARKENETH],
+            expected_symbols
+        );
+        assert_eq!(
+            symbols_for_data_sources[&DataSources::DSUNISWAPETH],
+            vec!["WETH".to_string()]
+        );
+
+        // Test malformed symbol case
+        let symbols = vec![
+            "WB TC".to_string(),
+            "WBTC\n".to_string(),
+            "\tXOR".to_string(),
+        ];
+        assert!(get_symbols_for_data_sources(&symbols, &[]).is_empty());
+
+        // Test too long symbol case
+        let symbols = vec!["A".repeat(100)];
+        assert!(get_symbols_for_data_sources(&symbols, &[]).is_empty());
+
+        // Test duplicated symbol case
+        let symbols = vec!["WBTC".to_string(), "WBTC".to_string(), "wbtc".to_string()];
+        let symbols_for_data_sources = get_symbols_for_data_sources(&symbols, &[]);
+        assert_eq!(
+            symbols_for_data_sources[&DataSources::DS1INCHETH],
+            vec!["WBTC".to_string()]
+        );
+        assert_eq!(
+            symbols_for_data_sources[&DataSources::DSARKENETH],
+            vec!["WBTC".to_string()]
+        );
+
+        // Test alias case
+        let symbols = vec!["BTC".to_string(), "DOGE".to_string()];
+        let symbols_for_data_sources = get_symbols_for_data_sources(&symbols, &[]);
+        assert_eq!(symbols_for_data_sources.len(), 3);
+        assert_eq!(
+            symbols_for_data_sources[&DataSources::DS1INCHETH],
+            vec!["WBTC".to_string()]
+        );
+
+        // Test source mask case
+        let symbols = vec!["WBTC".to_string(), "XOR".to_string()];
+        let symbols_for_data_sources = get_symbols_for_data_sources(&symbols, &[0b010]);
+        assert_eq!(symbols_for_data_sources.len(), 2);
+        assert_eq!(
+            symbols_for_data_sources[&DataSources::DSARKENETH],
+            vec!["WBTC".to_string(), "XOR".to_string()]
+        );
+        assert_eq!(
+            symbols_for_data_sources[&DataSources::DS1INCHETH],
+            vec!["XOR".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_is_source_selected() {
+        // Test all sources case
+        assert!(is_source_selected("WBTC", DataSources::DS1INCHETH, 0));
+        assert!(is_source_selected("WBTC", DataSources::DSUNISWAPETH, 0));
+
+        // Test masked case
+        assert!(is_source_selected("WBTC", DataSources::DSARKENETH, 0b010));
+        assert!(!is_source_selected("WBTC", DataSources::DS1INCHETH, 0b010));
+        assert!(!is_source_selected(
+            "WBTC",
+            DataSources::DSUNISWAPETH,
+            0b010
+        ));
+        assert!(is_source_selected("btc", DataSources::DSUNISWAPETH, 0b101));
+
+        // Test unconfigured source case
+        assert!(!is_source_selected("VC", DataSources::DSARKENBSC, 0b11));
+        assert!(!is_source_selected("DNE", DataSources::DS1INCHETH, 0b1));
+    }
+
+    #[test]
+    fn test_get_request_plan() {
+        let symbols = vec![
+            "XOR".to_string(),
+            "PHB".to_string(),
+            "WBTC".to_string(),
+            "VC".to_string(),
+        ];
+        let plan = get_request_plan(&symbols, &[]);
+        let expected_plan = vec![
+            (
+                DataSources::DS1INCHETH,
+                vec!["WBTC".to_string(), "XOR".to_string()],
+            ),
+            (
+                DataSources::DSARKENETH,
+                vec!["WBTC".to_string(), "XOR".to_string()],
+            ),
+            (
+                DataSources::DS1INCHBSC,
+                vec!["PHB".to_string(), "VC".to_string()],
+            ),
+            (DataSources::DSARKENBSC, vec!["PHB".to_string()]),
+            (DataSources::DSUNISWAPETH, vec!["WBTC".to_string()]),
+        ];
+        assert_eq!(plan, expected_plan);
+
+        // Test order independence case
+        let reversed_symbols = symbols.into_iter().rev().collect::<Vec<String>>();
+        assert_eq!(get_request_plan(&reversed_symbols, &[]), expected_plan);
+    }
+
+    #[test]
+    fn test_plan_requests() {
+        // Test all supported case
+        let symbols = vec!["BTC".to_string(), "VC".to_string()];
+        let (plan, unsupported_symbols) = plan_requests(&symbols, &[]);
+        assert_eq!(
+            plan,
+            vec![
+                (715, vec!["WBTC".to_string()]),
+                (716, vec!["WBTC".to_string()]),
+                (717, vec!["VC".to_string()]),
+                (719, vec!["WBTC".to_string()]),
+            ]
+        );
+        assert!(unsupported_symbols.is_empty());
+
+        // Test unsupported symbols case
+        let symbols = vec![
+            "DNE".to_string(),
+            "XOR".to_string(),
+            "FOO".to_string(),
+            "weth".to_string(),
+        ];
+        let (plan, unsupported_symbols) = plan_requests(&symbols, &[]);
+        assert_eq!(plan.len(), 3);
+        assert_eq!(
+            unsupported_symbols,
+            vec!["DNE".to_string(), "FOO".to_string()]
+        );
+
+        // Test too long symbol case
+        let symbols = vec!["A".repeat(100), "VC".to_string()];
+        let (plan, unsupported_symbols) = plan_requests(&symbols, &[]);
+        assert_eq!(plan, vec![(717, vec!["VC".to_string()])]);
+        assert_eq!(unsupported_symbols, vec!["A".repeat(100)]);
+
+        // Test empty case
+        assert_eq!(plan_requests(&[], &[]), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_get_decimals() {
+        // Test configured case
+        assert_eq!(get_decimals("PSWAP"), 18);
+        assert_eq!(get_decimals("WBTC"), 6);
+
+        // Test fallback case
+        assert_eq!(get_decimals("WETH"), DEFAULT_DECIMALS);
+        assert_eq!(10u64.pow(DEFAULT_DECIMALS), MULTIPLIER);
+    }
+
+    #[test]
+    fn test_response_decimals() {
+        // Test symbol scaled by 1e6 case
+        let response = Response::new("WBTC".into(), ResponseCode::Success, 30000000000, 3);
+        assert_eq!(response.decimals, 6);
+
+        // Test alias case
+        let response = Response::new("BTC".into(), ResponseCode::Success, 30000000000, 3);
+        assert_eq!(response.decimals, 6);
+
+        // Test default and unsupported case
+        let response = Response::new("WETH".into(), ResponseCode::Success, MULTIPLIER, 3);
+        assert_eq!(response.decimals, 9);
+        let response = Response::new("DNE".into(), ResponseCode::SymbolNotSupported, 0, 0);
+        assert_eq!(response.decimals, DEFAULT_DECIMALS as u8);
+    }
+
+    #[test]
+    fn test_get_multiplier() {
+        // Test configured case
+        assert_eq!(get_multiplier("PSWAP"), 1000000000000000000);
+        assert_eq!(get_multiplier("WBTC"), 1000000);
+
+        // Test fallback case
+        assert_eq!(get_multiplier("WETH"), MULTIPLIER);
+        assert_eq!(get_multiplier("DNE"), MULTIPLIER);
+    }
+
+    #[test]
+    fn test_aggregate_value_with_multiplier() {
+        let params = AggregationParams {
+            minimum_source_count: 1,
+            ..Default::default()
+        };
+
+        // Test 1e18 case keeps digits that 1e9 truncates
+        let data = vec![0.0000000005];
+        let res = aggregate_value(
+            &data,
+            &[1],
+            &AggregationParams {
+                multiplier: get_multiplier("PSWAP"),
+                ..params
+            },
+        );
+        assert_eq!(res.unwrap().rate, 500000000);
+        let res = aggregate_value(&data, &[1], &params);
+        assert_eq!(res.unwrap().rate, 0);
+
+        // Test 1e18 case overflows on large values
+        let data = vec![100.0];
+        let res = aggregate_value(
+            &data,
+            &[1],
+            &AggregationParams {
+                multiplier: get_multiplier("PSWAP"),
+                ..params
+            },
+        );
+        assert_eq!(res.unwrap_err(), ResponseCode::Overflow);
+
+        // Test 1e6 case fits values that overflow at 1e9
+        let data = vec![100000000000.0];
+        let res = aggregate_value(
+            &data,
+            &[1],
+            &AggregationParams {
+                multiplier: get_multiplier("WBTC"),
+                ..params
+            },
+        );
+        assert_eq!(res.unwrap().rate, 100000000000000000);
+        let res = aggregate_value(&data, &[1], &params);
+        assert_eq!(res.unwrap_err(), ResponseCode::Overflow);
+    }
+
+    #[test]
+    fn test_reject_too_many_symbols() {
+        // Test within limit case
+        let symbols = vec!["WBTC".to_string(); MAX_SYMBOLS];
+        assert_eq!(reject_too_many_symbols(&symbols), None);
+
+        // Test too many symbols case
+        let symbols = vec!["WBTC".to_string(); MAX_SYMBOLS + 1];
+        let responses = reject_too_many_symbols(&symbols).unwrap();
+        assert_eq!(responses.len(), MAX_SYMBOLS + 1);
+        assert!(responses
+            .iter()
+            .all(|r| r.response_code == ResponseCode::TooManySymbols as u8));
+
+        // Test expanded sentinel within limit case
+        let symbols = expand_symbols(&[ALL_SYMBOLS.to_string()]);
+        assert_eq!(reject_too_many_symbols(&symbols), None);
+    }
+
+    #[test]
+    fn test_reject_invalid_max_age() {
+        let symbols = vec!["WBTC".to_string(), "WETH".to_string()];
+
+        // Test valid case
+        assert_eq!(reject_invalid_max_age(&symbols, 0), None);
+        assert_eq!(reject_invalid_max_age(&symbols, MAX_AGE_SECONDS), None);
+
+        // Test out of range case
+        let responses = reject_invalid_max_age(&symbols, MAX_AGE_SECONDS + 1).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert!(responses
+            .iter()
+            .all(|r| r.response_code == ResponseCode::InvalidMaxAge as u8));
+    }
+
+    #[test]
+    fn test_split_symbols() {
+        // Test short list case
+        let symbols = vec!["WBTC".to_string(), "WETH".to_string()];
+        assert_eq!(split_symbols(symbols.clone(), 0), vec![symbols]);
+
+        // Test long list case
+        let symbols = (0..40)
+            .map(|i| format!("SYMBOL{:02}", i))
+            .collect::<Vec<String>>();
+        let chunks = split_symbols(symbols.clone(), 0);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.concat(), symbols);
+        assert!(chunks
+            .iter()
+            .all(|chunk| get_calldata(chunk, 0).len() <= MAX_CALLDATA_BYTES));
+
+        // Test max age case, which leaves less room for symbols
+        let chunks = split_symbols(symbols[..28].to_vec(), 0);
+        assert_eq!(chunks.len(), 1);
+        let chunks = split_symbols(symbols[..28].to_vec(), 60);
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks
+            .iter()
+            .all(|chunk| get_calldata(chunk, 60).len() <= MAX_CALLDATA_BYTES));
+
+        // Test oversized symbol case
+        let symbols = vec!["X".repeat(MAX_CALLDATA_BYTES + 1), "WBTC".to_string()];
+        assert_eq!(split_symbols(symbols.clone(), 0).len(), 2);
+    }
+
+    #[test]
+    fn test_get_input_requests() {
+        // Test regular case
+        let symbols = vec!["VC".to_string()];
+        let input = Input {
+            quote_symbol: "BETH".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            get_input_requests(&symbols, &input),
+            vec![
+                (
+                    717,
+                    DataSources::DS1INCHBSC,
+                    vec!["BETH".to_string(), "VC".to_string()]
+                ),
+                (718, DataSources::DSARKENBSC, vec!["BETH".to_string()]),
+            ]
+        );
+
+        // Test multiple quote symbols case
+        let input = Input {
+            quote_symbol: "BETH".to_string(),
+            quote_symbols: vec!["PHB".to_string(), "VC".to_string()],
+            ..Default::default()
+        };
+        let requests = get_input_requests(&symbols, &input);
+        assert_eq!(
+            requests[0],
+            (
+                717,
+                DataSources::DS1INCHBSC,
+                vec!["BETH".to_string(), "PHB".to_string(), "VC".to_string()]
+            )
+        );
+
+        // Test health check case
+        let symbols = vec!["VC".to_string(), HEALTH_SYMBOL.to_string()];
+        let input = Input {
+            source_masks: vec![0b001],
+            ..Default::default()
+        };
+        let requests = get_input_requests(&symbols, &input);
+        let requested_symbols = requests
+            .iter()
+            .flat_map(|(_, _, symbols)| symbols.iter().cloned())
+            .collect::<HashSet<String>>();
+        assert_eq!(requested_symbols.len(), SYMBOLS.len());
+        let data_sources = requests
+            .iter()
+            .map(|(_, ds, _)| *ds)
+            .collect::<HashSet<DataSources>>();
+        assert_eq!(data_sources.len(), DATA_SOURCE_COUNT);
+    }
+
+    #[test]
+    fn test_get_health_responses() {
+        let requests = vec![
+            (
+                715,
+                DataSources::DS1INCHETH,
+                vec!["WBTC".to_string(), "XOR".to_string()],
+            ),
+            (717, DataSources::DS1INCHBSC, vec!["VC".to_string()]),
+            (1715, DataSources::DS1INCHETH, vec!["WETH".to_string()]),
+            (718, DataSources::DSARKENBSC, vec!["VC".to_string()]),
+        ];
+        let raw_outputs = vec![
+            vec![
+                "1.0,2.0".to_string(),
+                "bad".to_string(),
+                "1.0,-".to_string(),
+            ],
+            vec!["0.05".to_string(), "0.05".to_string(), "0.05".to_string()],
+            vec!["3.0".to_string(), "3.0,x".to_string()],
+            vec![],
+        ];
+
+        // Test mixed source responses case
+        assert_eq!(
+            get_health_responses(&requests, &raw_outputs),
+            vec![
+                Response::new("715".to_string(), ResponseCode::Health, 0, 1),
+                Response::new("717".to_string(), ResponseCode::Health, 0, 3),
+                Response::new("718".to_string(), ResponseCode::Health, 0, 0),
+            ]
+        );
+
+        // Test partially malformed multi-row report case
+        let raw_outputs = vec![
+            vec!["1.0,2.0\nbad".to_string(), "bad\n1.0".to_string()],
+            vec!["0.05\n0.05,x,y".to_string()],
+            vec!["3.0".to_string(), "3.0".to_string()],
+            vec![],
+        ];
+        assert_eq!(
+            get_health_responses(&requests, &raw_outputs),
+            vec![
+                Response::new("715".to_string(), ResponseCode::Health, 0, 1),
+                Response::new("717".to_string(), ResponseCode::Health, 0, 1),
+                Response::new("718".to_string(), ResponseCode::Health, 0, 0),
+            ]
+        );
+
+        // Test no requests case
+        assert!(get_health_responses(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_get_external_requests() {
+        let symbols = ["WBTC", "WETH"]
+            .repeat(30)
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        let plan = vec![
+            (DataSources::DS1INCHETH, symbols),
+            (DataSources::DSARKENETH, vec!["XOR".to_string()]),
+        ];
+
+        // Test split request case
+        let requests = get_external_requests(plan, 0);
+        let external_ids = requests.iter().map(|(id, _, _)| *id).collect::<Vec<i64>>();
+        assert_eq!(external_ids, vec![715, 1715, 716]);
+        assert!(requests[..2]
+            .iter()
+            .all(|(_, ds, _)| *ds == DataSources::DS1INCHETH));
+
+        // Test recombining split responses case
+        let env = MockEnv {
+            inputs: requests
+                .iter()
+                .map(|(id, _, symbols)| {
+                    let output = vec!["1.5"; symbols.len()].join(",");
+                    (*id, vec![output.clone(), output])
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let raw_outputs = load_raw_outputs(&env, &requests);
+        let plan = requests
+            .into_iter()
+            .map(|(_, ds, symbols)| (ds, symbols))
+            .collect();
+        let source_data = collect_all_source_data(plan, &raw_outputs, 2, 2, 0, EvenRule::Average);
+        for symbol in ["WBTC", "WETH"] {
+            assert_eq!(
+                source_data.prices[symbol],
+                vec![(DataSources::DS1INCHETH, 1.5); 30]
+            );
+        }
+        assert_eq!(
+            source_data.prices["XOR"],
+            vec![(DataSources::DSARKENETH, 1.5)]
+        );
+    }
+
+    #[test]
+    fn test_symbols_for_source() {
+        // Test single chain source case
+        assert_eq!(symbols_for_source(717), vec!["BETH", "PHB", "VC"]);
+
+        // Test every symbol being listed by its sources case
+        for (symbol, config) in SYMBOLS.entries() {
+            for ds in config.sources {
+                assert!(symbols_for_source(*ds as i64).contains(symbol));
+            }
+        }
+
+        // Test unknown data source case
+        assert!(symbols_for_source(1).is_empty());
+    }
+
+    #[test]
+    fn test_expected_length() {
+        let symbols = vec![
+            "WBTC".to_string(),
+            "VC".to_string(),
+            "XOR".to_string(),
+            "DNE".to_string(),
+        ];
+
+        // Test routed symbols case
+        for (ds, routed_symbols) in get_request_plan(&symbols, &[]) {
+            assert_eq!(expected_length(ds as i64, &symbols), routed_symbols.len());
+        }
+        assert_eq!(expected_length(715, &symbols), 2);
+        assert_eq!(expected_length(717, &symbols), 1);
+
+        // Test unknown data source case
+        assert_eq!(expected_length(1, &symbols), 0);
+
+        // Test no symbols case
+        assert_eq!(expected_length(715, &[]), 0);
+    }
+
+    #[test]
+    fn test_validate_input() {
+        // Test valid case
+        let input = Input {
+            symbols: vec!["WBTC".to_string(), "XOR".to_string()],
+            minimum_source_count: 2,
+            minimum_source_counts: vec![150],
+            source_masks: vec![0b011, 0],
+            ..Default::default()
+        };
+        assert_eq!(validate_input(&input), Ok(()));
+
+        // Test multiple problems case
+        let input = Input {
+            symbols: vec!["WBTC".to_string(), "DNE".to_string(), "FOO".to_string()],
+            minimum_source_count: 6,
+            invert: vec![false; 4],
+            aggregation_method: AggregationMethodCode(200),
+            even_rule: EvenRuleCode(3),
+            max_age_seconds: MAX_AGE_SECONDS + 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_input(&input),
+            Err(vec![
+                "symbol DNE is not supported".to_string(),
+                "symbol FOO is not supported".to_string(),
+                "invert has 4 values for 3 symbols".to_string(),
+                "minimum source count 6 exceeds the 5 data sources".to_string(),
+                "aggregation method 200 is not supported".to_string(),
+                "even rule 3 is not supported".to_string(),
+                "max age 86401 exceeds 86400 seconds".to_string(),
+            ])
+        );
+
+        // Test skip unsupported case
+        let input = Input {
+            symbols: vec!["DNE".to_string()],
+            quote_symbol: "FOO".to_string(),
+            skip_unsupported: true,
+            rounding: RoundingModeCode(9),
+            methods: vec![AggregationMethodCode(0), AggregationMethodCode(99)],
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_input(&input),
+            Err(vec![
+                "quote symbol FOO is not supported".to_string(),
+                "method 99 is not supported".to_string(),
+                "rounding mode 9 is not supported".to_string(),
+            ])
+        );
+
+        // Test symbol count case
+        assert_eq!(
+            validate_input(&Input::default()),
+            Err(vec!["no symbols requested".to_string()])
+        );
+        let input = Input {
+            symbols: vec!["WBTC".to_string(); MAX_SYMBOLS + 1],
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_input(&input),
+            Err(vec![
+                "33 symbols requested, at most 32 are allowed".to_string()
+            ])
+        );
+
+        // Test all symbols case
+        let input = Input {
+            symbols: vec![ALL_SYMBOLS.to_string()],
+            previous_rates: vec![0; 2],
+            ..Default::default()
+        };
+        assert_eq!(validate_input(&input), Ok(()));
+    }
+
+    #[test]
+    fn test_get_calldata() {
+        let symbols = vec!["WBTC".to_string(), "WETH".to_string()];
+
+        // Test no max age case
+        assert_eq!(get_calldata(&symbols, 0), "WBTC WETH");
+
+        // Test max age case
+        assert_eq!(get_calldata(&symbols, 60), "WBTC WETH --max-age=60");
+    }
+
+    #[test]
+    fn test_reject_empty_request() {
+        // Test non-empty case
+        let symbols = vec!["WBTC".to_string()];
+        assert_eq!(reject_empty_request(&symbols), None);
+
+        // Test empty case
+        let responses = reject_empty_request(&[]).unwrap();
+        assert_eq!(
+            responses,
+            vec![Response::new(
+                String::new(),
+                ResponseCode::EmptyRequest,
+                0,
+                0
+            )]
+        );
+        let output = Output::new(responses, Vec::new(), 0);
+        assert_eq!(output.batch_code, ResponseCode::EmptyRequest as u8);
+    }
+
+    #[test]
+    fn test_data_source_count() {
+        // Test capacity hint covers every data source referenced by a symbol
+        let data_sources = SYMBOLS
+            .values()
+            .flat_map(|config| config.sources.iter())
+            .collect::<HashSet<&DataSources>>();
+        assert!(data_sources.len() <= DATA_SOURCE_COUNT);
+    }
+
+    #[test]
+    fn test_get_batch_code() {
+        // Test all success case
+        let responses = vec![
+            Response::new("WBTC".to_string(), ResponseCode::Success, 1, 2),
+            Response::new("XOR".to_string(), ResponseCode::Success, 1, 2),
+        ];
+        assert_eq!(get_batch_code(&responses), ResponseCode::Success);
+
+        // Test mixed case
+        let responses = vec![
+            Response::new("WBTC".to_string(), ResponseCode::Success, 1, 2),
+            Response::new("XOR".to_string(), ResponseCode::StaleData, 0, 2),
+            Response::new("foo".to_string(), ResponseCode::SymbolNotSupported, 0, 0),
+            Response::new("VC".to_string(), ResponseCode::NotEnoughSources, 0, 1),
+        ];
+        assert_eq!(get_batch_code(&responses), ResponseCode::SymbolNotSupported);
+
+        // Test empty case
+        assert_eq!(get_batch_code(&[]), ResponseCode::Success);
+
+        // Test output case
+        let output = Output::new(responses, Vec::new(), 0);
+        assert_eq!(output.batch_code, ResponseCode::SymbolNotSupported as u8);
+    }
+
+    #[test]
+    fn test_output_resolve_time() {
+        // Test given resolve time case
+        let output = Output::new(vec![], vec![], 0);
+        assert_eq!(output.resolve_time, 0);
+
+        // Test resolve time serialization case
+        let encoded = output.try_to_vec().unwrap();
+        assert_eq!(
+            encoded,
+            vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(encoded[9..17], 0u64.to_be_bytes());
+
+        // Test stubbed block time case
+        let env = MockEnv {
+            min_count: 1,
+            ask_count: 1,
+            inputs: HashMap::from([(717, vec!["0.05".to_string()])]),
+            ..Default::default()
+        };
+        let input = || Input {
+            symbols: vec!["VC".to_string()],
+            minimum_source_count: 1,
+            ..Default::default()
+        };
+        let output = execute_impl(&env, input());
+        assert_eq!(output.batch_code, ResponseCode::Success as u8);
+        assert_eq!(output.resolve_time, 0);
+
+        // Test block time case
+        let env = MockEnv {
+            execute_time: Some(1700000000),
+            ..env
+        };
+        let output = execute_impl(&env, input());
+        assert_eq!(output.resolve_time, 1700000000);
+        let encoded = output.try_to_vec().unwrap();
+        assert!(encoded
+            .windows(8)
+            .any(|bytes| bytes == 1700000000u64.to_be_bytes()));
+
+        // Test negative block time case
+        let env = MockEnv {
+            execute_time: Some(-1),
+            ..env
+        };
+        assert_eq!(execute_impl(&env, input()).resolve_time, 0);
+
+        // Test negative ask count case
+        let env = MockEnv {
+            ask_count: -1,
+            ..env
+        };
+        assert_eq!(
+            execute_impl(&env, input()).batch_code,
+            ResponseCode::Success as u8
+        );
+
+        // Test zero ask count case
+        let env = MockEnv {
+            ask_count: 0,
+            ..env
+        };
+        assert_eq!(
+            execute_impl(&env, input()).batch_code,
+            ResponseCode::Success as u8
+        );
+    }
+
+    #[test]
+    fn test_output_truncated_to() {
+        // Test output within limit case
+        let output = Output::new(
+            vec![Response::new(
+                "BTC".to_string(),
+                ResponseCode::Success,
+                1,
+                1,
+            )],
+            vec![vec![1, 2]],
+            0,
+        )
+        .truncated_to(MAX_OUTPUT_BYTES);
+        assert!(!output.truncated);
+        assert_eq!(output.debug_rates, vec![vec![1, 2]]);
+
+        // Test large debug batch case
+        let responses = (0..MAX_SYMBOLS)
+            .map(|i| {
+                Response::new(format!("SYM{}", i), ResponseCode::NotEnoughSources, 0, 0)
+                    .with_reason("x".repeat(100))
+            })
+            .collect::<Vec<Response>>();
+        let debug_rates = vec![vec![MULTIPLIER; DATA_SOURCE_COUNT]; MAX_SYMBOLS];
+        let output = Output::new(responses, debug_rates, 0);
+        assert!(output.encoded_len() > MAX_OUTPUT_BYTES);
+        let output = output.truncated_to(MAX_OUTPUT_BYTES);
+        assert!(output.truncated);
+        assert!(output.encoded_len() <= MAX_OUTPUT_BYTES);
+        assert!(output.debug_rates.is_empty());
+        assert_eq!(output.responses.len(), MAX_SYMBOLS);
+        assert!(output.responses.iter().all(
+            |r| r.reason.is_empty() && r.response_code == ResponseCode::NotEnoughSources as u8
+        ));
+    }
+
+    #[test]
+    fn test_execute_impl() {
+        let env = MockEnv {
+            min_count: 3,
+            ask_count: 3,
+            execute_time: Some(1700000000),
+            inputs: HashMap::from([(
+                717,
+                vec!["0.049".to_string(), "0.05".to_string(), "0.051".to_string()],
+            )]),
+        };
+
+        // Test empty request case
+        let output = execute_impl(&env, Input::default());
+        assert_eq!(output.batch_code, ResponseCode::EmptyRequest as u8);
+        assert_eq!(output.resolve_time, 1700000000);
+
+        // Test mocked data source case
+        let input = Input {
+            symbols: vec!["VC".to_string(), "DNE".to_string()],
+            minimum_source_count: 1,
+            ..Default::default()
+        };
+        let output = execute_impl(&env, input);
+        assert_eq!(
+            output,
+            Output::new(
+                vec![
+                    Response::new("VC".to_string(), ResponseCode::Success, 50000000, 1)
+                        .with_chain_id(Chain::Bsc),
+                    Response::new("DNE".to_string(), ResponseCode::SymbolNotSupported, 0, 0),
+                ],
+                vec![],
+                1700000000
+            )
+        );
+
+        // Test health check case
+        let bsc_outputs = vec!["1,2,3".to_string(), "1,2,3".to_string(), "1,2".to_string()];
+        let health_env = MockEnv {
+            inputs: HashMap::from([(717, bsc_outputs)]),
+            ..env.clone()
+        };
+        let input = Input {
+            symbols: vec![HEALTH_SYMBOL.to_string(), "VC".to_string()],
+            strict: true,
+            ..Default::default()
+        };
+        let output = execute_impl(&health_env, input);
+        assert_eq!(output.batch_code, ResponseCode::Health as u8);
+        assert_eq!(
+            output
+                .responses
+                .iter()
+                .map(|r| (r.symbol.as_str(), r.response_code, r.source_count))
+                .collect::<Vec<(&str, u8, u8)>>(),
+            vec![
+                ("715", ResponseCode::Health as u8, 0),
+                ("716", ResponseCode::Health as u8, 0),
+                ("717", ResponseCode::Health as u8, 2),
+                ("718", ResponseCode::Health as u8, 0),
+                ("719", ResponseCode::Health as u8, 0),
+            ]
+        );
+
+        // Test symbol indices case
+        let vc = get_symbol_table().iter().position(|s| *s == "VC").unwrap() as u16;
+        let input = Input {
+            symbol_indices: vec![vc, u16::MAX],
+            minimum_source_count: 1,
+            ..Default::default()
+        };
+        let output = execute_impl(&env, input);
+        assert_eq!(
+            output.responses,
+            vec![
+                Response::new("VC".to_string(), ResponseCode::Success, 50000000, 1)
+                    .with_chain_id(Chain::Bsc),
+                Response::new("#65535".to_string(), ResponseCode::SymbolNotSupported, 0, 0),
+            ]
+        );
+
+        // Test unsupported even rule case
+        let input = Input {
+            symbols: vec!["VC".to_string()],
+            minimum_source_count: 1,
+            even_rule: EvenRuleCode(3),
+            ..Default::default()
+        };
+        let output = execute_impl(&env, input);
+        assert_eq!(output.batch_code, ResponseCode::UnsupportedEvenRule as u8);
+        assert_eq!(output.responses[0].rate, 0);
+
+        // Test invalid max age case
+        let input = Input {
+            symbols: vec!["VC".to_string()],
+            max_age_seconds: MAX_AGE_SECONDS + 1,
+            ..Default::default()
+        };
+        let output = execute_impl(&env, input);
+        assert_eq!(output.batch_code, ResponseCode::InvalidMaxAge as u8);
+
+        // Test all-null single source case
+        let empty_env = MockEnv {
+            inputs: HashMap::from([(717, vec!["-".to_string(), "-".to_string()])]),
+            ..env.clone()
+        };
+        let input = Input {
+            symbols: vec!["VC".to_string()],
+            minimum_source_count: 1,
+            ..Default::default()
+        };
+        let output = execute_impl(&empty_env, input);
+        assert_eq!(
+            output.responses,
+            vec![Response::new(
+                "VC".to_string(),
+                ResponseCode::SourceEmpty,
+                0,
+                0
+            )]
+        );
+
+        // Test strict case
+        let input = Input {
+            symbols: vec!["VC".to_string(), "DNE".to_string()],
+            minimum_source_count: 1,
+            strict: true,
+            ..Default::default()
+        };
+        let output = execute_impl(&env, input);
+        assert_eq!(output.batch_code, ResponseCode::BatchRejected as u8);
+        assert!(output
+            .responses
+            .iter()
+            .all(|r| r.response_code == ResponseCode::BatchRejected as u8 && r.rate == 0));
+        let input = Input {
+            symbols: vec!["VC".to_string()],
+            minimum_source_count: 1,
+            strict: true,
+            ..Default::default()
+        };
+        let output = execute_impl(&env, input);
+        assert_eq!(output.batch_code, ResponseCode::Success as u8);
+        assert_eq!(output.responses[0].rate, 50000000);
+        let input = Input {
+            symbols: vec!["VC".to_string()],
+            minimum_source_count: 2,
+            allow_degraded: true,
+            strict: true,
+            ..Default::default()
+        };
+        let output = execute_impl(&env, input);
+        assert_eq!(output.batch_code, ResponseCode::Degraded as u8);
+        assert_eq!(output.responses[0].rate, 50000000);
+
+        // Test two-source WBTC case, where too few validators report a Uniswap rate
+        let env = MockEnv {
+            min_count: 3,
+            ask_count: 3,
+            execute_time: Some(1700000000),
+            inputs: HashMap::from([
+                (
+                    715,
+                    vec![
+                        "29990".to_string(),
+                        "30000".to_string(),
+                        "30010".to_string(),
+                    ],
+                ),
+                (
+                    716,
+                    vec![
+                        "30090".to_string(),
+                        "30100".to_string(),
+                        "30110".to_string(),
+                    ],
+                ),
+                (
+                    719,
+                    vec!["-".to_string(), "-".to_string(), "30050".to_string()],
+                ),
+            ]),
+        };
+        let input = Input {
+            symbols: vec!["BTC".to_string()],
+            minimum_source_count: 2,
+            ..Default::default()
+        };
+        let output = execute_impl(&env, input);
+        assert_eq!(
+            output,
+            Output::new(
+                vec![
+                    Response::new("BTC".to_string(), ResponseCode::Success, 30050000000, 2)
+                        .with_spread(3327787)
+                        .with_chain_id(Chain::Eth)
+                ],
+                vec![],
+                1700000000
+            )
+        );
+
+        // Test WBTC restricted to Arken case
+        let input = Input {
+            symbols: vec!["WBTC".to_string()],
+            minimum_source_count: 1,
+            allow_degraded: true,
+            source_masks: vec![0b010],
+            ..Default::default()
+        };
+        assert_eq!(
+            get_request_plan(&input.symbols, &input.source_masks),
+            vec![(DataSources::DSARKENETH, vec!["WBTC".to_string()])]
+        );
+        let output = execute_impl(&env, input);
+        assert_eq!(
+            output.responses,
+            vec![
+                Response::new("WBTC".to_string(), ResponseCode::Degraded, 30100000000, 1)
+                    .with_chain_id(Chain::Eth)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reject_failed_batch() {
+        // Test all success case
+        let responses = vec![
+            Response::new("WBTC".to_string(), ResponseCode::Success, 1, 2).with_spread(3),
+            Response::new("VC".to_string(), ResponseCode::Success, 4, 1),
+        ];
+        assert_eq!(
+            reject_failed_batch(responses),
+            vec![
+                Response::new("WBTC".to_string(), ResponseCode::Success, 1, 2).with_spread(3),
+                Response::new("VC".to_string(), ResponseCode::Success, 4, 1),
+            ]
+        );
+
+        // Test warning case
+        let responses = vec![
+            Response::new("WBTC".to_string(), ResponseCode::SuccessWithWarning, 1, 2),
+            Response::new("VC".to_string(), ResponseCode::Degraded, 4, 1),
+        ];
+        assert_eq!(
+            reject_failed_batch(responses),
+            vec![
+                Response::new("WBTC".to_string(), ResponseCode::SuccessWithWarning, 1, 2),
+                Response::new("VC".to_string(), ResponseCode::Degraded, 4, 1),
+            ]
+        );
+
+        // Test partial failure case
+        let responses = vec![
+            Response::new("WBTC".to_string(), ResponseCode::Success, 1, 2).with_spread(3),
+            Response::new("VC".to_string(), ResponseCode::NotEnoughSources, 0, 0)
+                .with_reason("only 0 of 1 sources responded".to_string()),
+        ];
+        assert_eq!(
+            reject_failed_batch(responses),
+            vec![
+                Response::new("WBTC".to_string(), ResponseCode::BatchRejected, 0, 2),
+                Response::new("VC".to_string(), ResponseCode::BatchRejected, 0, 0)
+                    .with_reason("only 0 of 1 sources responded".to_string()),
+            ]
+        );
+
+        // Test empty case
+        assert_eq!(reject_failed_batch(vec![]), vec![]);
+    }
+
+    #[test]
+    fn test_get_requested_symbols() {
+        let symbols = vec!["WBTC".to_string(), "XOR".to_string()];
+
+        // Test missing quote symbol case
+        let requested_symbols = get_requested_symbols(&symbols, "WETH");
+        assert_eq!(
+            requested_symbols,
+            vec!["WBTC".to_string(), "XOR".to_string(), "WETH".to_string()]
+        );
+
+        // Test present quote symbol case
+        let requested_symbols = get_requested_symbols(&symbols, "XOR");
+        assert_eq!(requested_symbols, symbols);
+
+        // Test no quote symbol case
+        let requested_symbols = get_requested_symbols(&symbols, "");
+        assert_eq!(requested_symbols, symbols);
+    }
+
+    #[test]
+    fn test_is_low_participation() {
+        // Test full participation case
+        assert!(!is_low_participation(16, 16));
+
+        // Test threshold case
+        assert!(!is_low_participation(16, 8));
+        assert!(!is_low_participation(3, 2));
+
+        // Test low participation case
+        assert!(is_low_participation(16, 7));
+        assert!(is_low_participation(3, 1));
+        assert!(is_low_participation(4, 0));
+
+        // Test zero ask count case
+        assert!(!is_low_participation(0, 0));
+
+        // Test overflow case
+        assert!(!is_low_participation(u64::MAX, usize::MAX));
+        assert!(is_low_participation(u64::MAX, 1));
+    }
+
+    #[test]
+    fn test_get_declared_minimum_source_count() {
+        // Test declared minimum case
+        assert_eq!(get_declared_minimum_source_count("WBTC"), 2);
+
+        // Test no declared minimum case
+        assert_eq!(get_declared_minimum_source_count("XOR"), 0);
+
+        // Test unknown symbol case
+        assert_eq!(get_declared_minimum_source_count("foo"), 0);
+    }
+
+    #[test]
+    fn test_get_declared_aggregation_method() {
+        // Test declared method case
+        assert_eq!(
+            get_declared_aggregation_method("RLB"),
+            Some(AggregationMethod::TrimmedMean)
+        );
+
+        // Test no declared method case
+        assert_eq!(get_declared_aggregation_method("XOR"), None);
+
+        // Test unknown symbol case
+        assert_eq!(get_declared_aggregation_method("foo"), None);
+    }
+
+    /// Asserts every symbol is requested from at least one data source, that every data source
+    /// it references is one the validators run and that every grouped symbol is supported
+    fn validate_symbol_wiring() {
+        let known_ids = HashSet::from([715, 716, 717, 718, 719]);
+        for (symbol, config) in SYMBOLS.entries() {
+            assert!(!config.sources.is_empty(), "{} has no data sources", symbol);
+            for ds in config.sources {
+                assert!(
+                    known_ids.contains(&(*ds as i64)),
+                    "{} references unknown data source {}",
+                    symbol,
+                    *ds as i64
+                );
+            }
+        }
+        for symbol in SYMBOLS.keys().chain(ALIASES.keys()) {
+            assert!(
+                symbol.len() <= MAX_SYMBOL_LEN,
+                "{} is longer than MAX_SYMBOL_LEN",
+                symbol
+            );
+        }
+        for group in GROUPS {
+            for symbol in [group.symbol, group.reference] {
+                assert!(
+                    SYMBOLS.contains_key(symbol),
+                    "{} is grouped but unknown",
+                    symbol
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_symbol_wiring() {
+        validate_symbol_wiring();
+    }
+
+    #[test]
+    fn test_collect_all_source_data() {
+        let symbols = vec![
+            "WBTC".to_string(),
+            "stETH".to_string(),
+            "wstETH".to_string(),
+            "WETH".to_string(),
+            "XOR".to_string(),
+            "RLB".to_string(),
+            "VAL".to_string(),
+            "VC".to_string(),
+            "PHB".to_string(),
+            "BETH".to_string(),
+        ];
+        let plan = get_request_plan(&symbols, &[]);
+        let raw_outputs = plan
+            .iter()
+            .map(|(ds, symbols)| {
+                (0..3)
+                    .map(|v| {
+                        symbols
+                            .iter()
+                            .enumerate()
+                            .map(|(i, _)| format!("{}.{}", i + 1, *ds as i64 + v))
+                            .collect::<Vec<String>>()
+                            .join(",")
+                    })
+                    .collect::<Vec<String>>()
+            })
+            .collect::<Vec<Vec<String>>>();
+
+        // Test output matches collecting each data source sequentially
+        let mut expected_source_data = SourceData::default();
+        for ((ds, symbols), raw_outputs) in zip(plan.clone(), &raw_outputs) {
+            collect_source_data(
+                &mut expected_source_data,
+                ds,
+                symbols,
+                raw_outputs,
+                2,
+                0,
+                EvenRule::Average,
+            );
+        }
+        let source_data = collect_all_source_data(plan, &raw_outputs, 2, 3, 0, EvenRule::Average);
+        let input = Input {
+            minimum_source_count: 1,
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses,
+            get_responses(&symbols, &expected_source_data, &input)
+        );
+        assert!(responses
+            .iter()
+            .all(|r| r.response_code == ResponseCode::Success as u8));
+
+        // Test three-source symbol all responding case
+        let symbols = vec!["WETH".to_string()];
+        let plan = get_request_plan(&symbols, &[]);
+        assert_eq!(plan.len(), 3);
+        let three_source_outputs = vec![
+            vec!["2.0".to_string(), "2.0".to_string(), "2.5".to_string()],
+            vec!["2.25".to_string(), "2.25".to_string(), "2.0".to_string()],
+            vec!["2.5".to_string(), "2.5".to_string(), "3.0".to_string()],
+        ];
+        let source_data =
+            collect_all_source_data(plan, &three_source_outputs, 2, 3, 0, EvenRule::Average);
+        let input = Input {
+            minimum_source_count: 3,
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses[0],
+            Response::new("WETH".to_string(), ResponseCode::Success, 2250000000, 3)
+                .with_spread(222222222)
+                .with_chain_id(Chain::Eth)
+        );
+
+        // Test three-source symbol one missing case
+        let plan = get_request_plan(&symbols, &[]);
+        let missing_source_outputs = vec![
+            three_source_outputs[0].clone(),
+            three_source_outputs[1].clone(),
+            vec!["-".to_string(), "-".to_string(), "3.0".to_string()],
+        ];
+        let source_data =
+            collect_all_source_data(plan, &missing_source_outputs, 2, 3, 0, EvenRule::Average);
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses[0].response_code,
+            ResponseCode::NotEnoughSources as u8
+        );
+        let input = Input {
+            minimum_source_count: 2,
+            ..Default::default()
+        };
+        let responses = get_responses(&symbols, &source_data, &input);
+        assert_eq!(
+            responses[0],
+            Response::new("WETH".to_string(), ResponseCode::Success, 2125000000, 2)
+                .with_spread(117647058)
+                .with_chain_id(Chain::Eth)
+        );
+
+        // Test low participation case
+        let plan = get_request_plan(&symbols, &[]);
+        let source_data = collect_all_source_data(plan, &raw_outputs, 2, 16, 0, EvenRule::Average);
+        assert_eq!(source_data.low_participation_symbols.len(), symbols.len());
     }
 }